@@ -1,12 +1,16 @@
 #![no_std]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub mod linked_hash_map;
 pub mod linked_hash_set;
 pub mod lru_cache;
 #[cfg(feature = "serde_impl")]
 pub mod serde;
+pub mod two_queue_cache;
 
 pub use linked_hash_map::LinkedHashMap;
 pub use linked_hash_set::LinkedHashSet;
 pub use lru_cache::LruCache;
+pub use two_queue_cache::TwoQueueCache;
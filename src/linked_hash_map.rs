@@ -4,14 +4,15 @@ use core::{
     cmp::Ordering,
     fmt,
     hash::{BuildHasher, Hash, Hasher},
-    iter::FromIterator,
+    iter::{FromIterator, Rev},
     marker::PhantomData,
     mem::{self, MaybeUninit},
-    ops::{Index, IndexMut},
+    ops::{ControlFlow, Index, IndexMut},
     ptr::{self, NonNull},
 };
 
-use alloc::boxed::Box;
+use alloc::{alloc::handle_alloc_error, vec::Vec};
+use allocator_api2::alloc::{Allocator, Global};
 use hashbrown::hash_map::DefaultHashBuilder;
 use hashbrown::hash_table::{self, HashTable};
 
@@ -20,6 +21,8 @@ pub enum TryReserveError {
     AllocError { layout: Layout },
 }
 
+pub use hashbrown::Equivalent;
+
 /// A version of `HashMap` that has a user controllable order for its entries.
 ///
 /// It achieves this by keeping its entries in an internal linked list and using a `HashMap` to
@@ -35,8 +38,8 @@ pub enum TryReserveError {
 /// * Methods that have the word `insert` will insert a new entry ot the back of the list, and if
 ///   that method might replace an entry, that method will *also move that existing entry to the
 ///   back*.
-pub struct LinkedHashMap<K, V, S = DefaultHashBuilder> {
-    table: HashTable<NonNull<Node<K, V>>>,
+pub struct LinkedHashMap<K, V, S = DefaultHashBuilder, A: Allocator = Global> {
+    table: HashTable<NonNull<Node<K, V>>, A>,
     // We always need to keep our custom hash builder outside of the HashTable, because it doesn't
     // know how to do any hashing itself.
     hash_builder: S,
@@ -92,6 +95,128 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Creates a map with room for `capacity` entries in its internal table and `node_count` nodes
+    /// already allocated on its internal free list.
+    ///
+    /// This is equivalent to calling `LinkedHashMap::reserve_nodes` after
+    /// `LinkedHashMap::with_capacity_and_hasher`, so that inserting up to that many entries does
+    /// not allocate.
+    #[inline]
+    pub fn with_capacity_and_hasher_and_nodes(
+        capacity: usize,
+        hash_builder: S,
+        node_count: usize,
+    ) -> Self {
+        let mut map = Self::with_capacity_and_hasher(capacity, hash_builder);
+        map.reserve_nodes(node_count);
+        map
+    }
+
+    /// Moves every entry of `other` to the *back* of this map in order, re-linking the nodes of
+    /// `other` rather than allocating new ones.
+    ///
+    /// Keys already present in this map are handled as by `LinkedHashMap::insert`: the value is
+    /// replaced and the entry is moved to the back, and the node from `other` is kept on the
+    /// internal free list.  Keys are re-hashed with this map's hasher, but no entry is allocated,
+    /// so only the internal table may need to grow.
+    ///
+    /// This is only available for the global allocator, since nodes allocated by one map are
+    /// deallocated by the other.
+    pub fn extend_map(&mut self, mut other: Self)
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let other_values = match other.values {
+            Some(values) => values,
+            None => return,
+        };
+        self.reserve(other.len());
+
+        unsafe {
+            // Take ownership of the list of `other` up front, so that if hashing or comparing a key
+            // panics, the remaining entries are leaked rather than dropped twice.
+            let mut cur = other_values.as_ref().links.value.next;
+            let mut remaining = other.len();
+            other.table.clear();
+            detach_node(other_values);
+            (*other_values.as_ptr()).links.value = ValueLinks {
+                next: other_values,
+                prev: other_values,
+            };
+
+            ensure_guard_node(&mut self.values, self.table.allocator());
+            let guard = NonNull::new_unchecked(self.values.as_ptr());
+
+            while remaining > 0 {
+                remaining -= 1;
+                let mut node = cur;
+                cur = node.as_ref().links.value.next;
+
+                let hash = hash_key(&self.hash_builder, node.as_ref().key_ref());
+                let existing = self
+                    .table
+                    .find(hash, |o| o.as_ref().key_ref().eq(node.as_ref().key_ref()))
+                    .copied();
+                match existing {
+                    Some(mut existing) => {
+                        let (key, value) = node.as_mut().take_entry();
+                        push_free(&mut self.free, node);
+                        detach_node(existing);
+                        attach_before(existing, guard);
+                        let old = mem::replace(&mut existing.as_mut().entry_mut().1, value);
+                        drop((key, old));
+                    }
+                    None => {
+                        node.as_mut().hash = hash;
+                        attach_before(node, guard);
+                        self.table.insert_unique(hash, node, |&n| hash_node(n));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, V, A: Allocator> LinkedHashMap<K, V, DefaultHashBuilder, A> {
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_hasher_in(DefaultHashBuilder::default(), alloc)
+    }
+
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self::with_capacity_and_hasher_in(capacity, DefaultHashBuilder::default(), alloc)
+    }
+}
+
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A> {
+    #[inline]
+    pub fn with_hasher_in(hash_builder: S, alloc: A) -> Self {
+        Self {
+            hash_builder,
+            table: HashTable::new_in(alloc),
+            values: None,
+            free: None,
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity_and_hasher_in(capacity: usize, hash_builder: S, alloc: A) -> Self {
+        Self {
+            hash_builder,
+            table: HashTable::with_capacity_in(capacity, alloc),
+            values: None,
+            free: None,
+        }
+    }
+
+    /// Returns a reference to the allocator used to allocate the map's table and nodes.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.table.allocator()
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.table.len()
@@ -107,7 +232,7 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         self.table.clear();
         if let Some(mut values) = self.values {
             unsafe {
-                drop_value_nodes(values);
+                drop_value_nodes(values, self.table.allocator());
                 values.as_mut().links.value = ValueLinks {
                     prev: values,
                     next: values,
@@ -116,6 +241,49 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Removes all entries from the map, keeping the allocated capacity of both the internal table
+    /// and the nodes of the internal linked list.
+    ///
+    /// Unlike `LinkedHashMap::clear`, which deallocates the nodes of removed entries, the nodes are
+    /// placed on the internal free list so that refilling the map up to its previous length does
+    /// not allocate.
+    #[inline]
+    pub fn clear_retaining_capacity(&mut self) {
+        self.drain();
+    }
+
+    /// Replaces every value in the map with `V::default()`.
+    ///
+    /// All keys are kept in place, so the order of the internal linked list and the allocated nodes
+    /// are left untouched.
+    #[inline]
+    pub fn clear_values(&mut self)
+    where
+        V: Default,
+    {
+        for v in self.values_mut() {
+            *v = V::default();
+        }
+    }
+
+    /// Calls `f` with every entry of the map in order from front to back, allowing each value to be
+    /// modified in place.
+    ///
+    /// Neither the order of the map nor any of its allocations are changed.
+    #[inline]
+    pub fn map_values_in_place<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V),
+    {
+        for (k, v) in self.iter_mut() {
+            f(k, v);
+        }
+    }
+
+    /// Iterates over the entries from the front to the back of the internal linked list.
+    ///
+    /// All of the map's iterators are double ended, so iterating from back to front with `rev`
+    /// is just as cheap.
     #[inline]
     pub fn iter(&self) -> Iter<K, V> {
         let (head, tail) = if let Some(values) = self.values {
@@ -135,6 +303,33 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Iterates over the entries from the back to the front of the internal linked list.
+    ///
+    /// This is the same as `iter().rev()`.
+    #[inline]
+    pub fn iter_rev(&self) -> Rev<Iter<'_, K, V>> {
+        self.iter().rev()
+    }
+
+    /// Iterates over the entries at positions `start..end` of the internal linked list.
+    ///
+    /// `end` is clamped to the length of the map, and an empty or out of bounds range yields no
+    /// entries.  Finding the first and last entries walks the list, so this takes O(n) time.
+    pub fn range_positions(&self, start: usize, end: usize) -> Iter<'_, K, V> {
+        let end = end.min(self.len());
+        if start >= end {
+            return Iter::default();
+        }
+        let head = self.nth_node(start).unwrap();
+        let tail = self.nth_node(end - 1).unwrap();
+        Iter {
+            head: head.as_ptr(),
+            tail: tail.as_ptr(),
+            remaining: end - start,
+            marker: PhantomData,
+        }
+    }
+
     #[inline]
     pub fn iter_mut(&mut self) -> IterMut<K, V> {
         let (head, tail) = if let Some(values) = self.values {
@@ -154,6 +349,15 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Removes all entries from the map, returning them in order as an iterator.
+    ///
+    /// The map is emptied as soon as this is called.  Entries that have not been yielded are
+    /// dropped when the `Drain` is dropped, so if the `Drain` is leaked (e.g. with `mem::forget`)
+    /// those entries are leaked as well, but the map is left empty and valid.
+    ///
+    /// Like `LinkedHashMap::clear_retaining_capacity`, this keeps the capacity of the internal
+    /// table, and the nodes of drained entries are placed on the internal free list, so refilling
+    /// the map afterwards does not allocate.
     #[inline]
     pub fn drain(&mut self) -> Drain<'_, K, V> {
         unsafe {
@@ -198,6 +402,53 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Returns an iterator over the keys in sorted order, leaving the order of the map untouched.
+    ///
+    /// This collects references to every key into a temporary `Vec` and sorts it, so it allocates
+    /// and takes O(n log n) time.
+    #[inline]
+    pub fn keys_sorted(&self) -> impl Iterator<Item = &K>
+    where
+        K: Ord,
+    {
+        let mut keys = self.keys().collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.into_iter()
+    }
+
+    /// Returns an iterator over the values in the sorted order of their keys, leaving the order of
+    /// the map untouched.
+    ///
+    /// Like `LinkedHashMap::keys_sorted`, this allocates a temporary `Vec` and takes O(n log n)
+    /// time.
+    #[inline]
+    pub fn values_sorted_by_key(&self) -> impl Iterator<Item = &V>
+    where
+        K: Ord,
+    {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries.into_iter().map(|(_, v)| v)
+    }
+
+    /// Clones the entries of the map into a `Vec`, in order from front to back.
+    #[inline]
+    pub fn to_vec(&self) -> Vec<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+
+    /// Moves the entries of the map into a `Vec`, in order from front to back.
+    #[inline]
+    pub fn into_vec(self) -> Vec<(K, V)> {
+        self.into_iter().collect()
+    }
+
+    /// Returns the entry at the *front* of the internal linked list, without changing the order of
+    /// the map.
     #[inline]
     pub fn front(&self) -> Option<(&K, &V)> {
         if self.is_empty() {
@@ -210,6 +461,8 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Returns the entry at the *back* of the internal linked list, without changing the order of
+    /// the map.
     #[inline]
     pub fn back(&self) -> Option<(&K, &V)> {
         if self.is_empty() {
@@ -222,6 +475,94 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         }
     }
 
+    /// Returns the key at the *front* of the internal linked list.
+    #[inline]
+    pub fn front_key(&self) -> Option<&K> {
+        self.front().map(|(k, _)| k)
+    }
+
+    /// Returns the key at the *back* of the internal linked list.
+    #[inline]
+    pub fn back_key(&self) -> Option<&K> {
+        self.back().map(|(k, _)| k)
+    }
+
+    /// Returns the value at the *front* of the internal linked list.
+    #[inline]
+    pub fn front_value(&self) -> Option<&V> {
+        self.front().map(|(_, v)| v)
+    }
+
+    /// Returns the value at the *back* of the internal linked list.
+    #[inline]
+    pub fn back_value(&self) -> Option<&V> {
+        self.back().map(|(_, v)| v)
+    }
+
+    /// Returns the first entry in the map, named after `BTreeMap::first_key_value`.
+    ///
+    /// This is the same as `LinkedHashMap::front`; use `LinkedHashMap::pop_front` to remove it.
+    #[inline]
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.front()
+    }
+
+    /// Returns the last entry in the map, named after `BTreeMap::last_key_value`.
+    ///
+    /// This is the same as `LinkedHashMap::back`; use `LinkedHashMap::pop_back` to remove it.
+    #[inline]
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.back()
+    }
+
+    /// Returns the entry at the *front* of the internal linked list without changing the order of
+    /// the map.
+    ///
+    /// This is the same as `LinkedHashMap::front`.
+    #[inline]
+    pub fn peek_front(&self) -> Option<(&K, &V)> {
+        self.front()
+    }
+
+    /// Returns the entry at the *back* of the internal linked list without changing the order of
+    /// the map.
+    ///
+    /// This is the same as `LinkedHashMap::back`.
+    #[inline]
+    pub fn peek_back(&self) -> Option<(&K, &V)> {
+        self.back()
+    }
+
+    /// Returns the position in the internal linked list of the first entry whose key matches the
+    /// predicate, counting from the front.
+    ///
+    /// This walks the list from the front, so takes O(n) time.
+    #[inline]
+    pub fn position_by_key<F>(&self, pred: F) -> Option<usize>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.keys().position(pred)
+    }
+
+    /// Returns the position in the internal linked list of the first entry whose value matches the
+    /// predicate, counting from the front.
+    ///
+    /// This walks the list from the front, so takes O(n) time.
+    #[inline]
+    pub fn position_by_value<F>(&self, pred: F) -> Option<usize>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        self.values().position(pred)
+    }
+
+    /// Retains only the entries for which the predicate returns true.
+    ///
+    /// Entries are visited in the order of the internal table, *not* in the order of the internal
+    /// linked list.  If the predicate panics, the entries it has already rejected are removed and
+    /// dropped, every other entry is kept in its original position, and the map is left in a
+    /// consistent state.
     #[inline]
     pub fn retain<F>(&mut self, mut f: F)
     where
@@ -244,122 +585,687 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
         });
     }
 
+    /// Like `LinkedHashMap::retain`, but afterwards deallocates free nodes until at most
+    /// `max_free_nodes` remain, see `LinkedHashMap::shrink_free_list`.
+    ///
+    /// Removing many entries otherwise leaves all of their nodes on the internal free list, which
+    /// makes later insertions cheaper but holds on to that memory until the map is shrunk.
     #[inline]
-    pub fn hasher(&self) -> &S {
-        &self.hash_builder
-    }
-
-    #[inline]
-    pub fn capacity(&self) -> usize {
-        self.table.capacity()
-    }
-}
-
-impl<K, V, S> LinkedHashMap<K, V, S>
-where
-    K: Eq + Hash,
-    S: BuildHasher,
-{
-    #[inline]
-    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
-        match self.raw_entry_mut().from_key(&key) {
-            RawEntryMut::Occupied(occupied) => Entry::Occupied(OccupiedEntry {
-                key,
-                raw_entry: occupied,
-            }),
-            RawEntryMut::Vacant(vacant) => Entry::Vacant(VacantEntry {
-                key,
-                raw_entry: vacant,
-            }),
-        }
-    }
-
-    #[inline]
-    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    pub fn retain_and_compact<F>(&mut self, f: F, max_free_nodes: usize)
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        F: FnMut(&K, &mut V) -> bool,
     {
-        self.raw_entry().from_key(k).map(|(_, v)| v)
+        self.retain(f);
+        self.shrink_free_list(max_free_nodes);
     }
 
+    /// Like `LinkedHashMap::retain`, but only passes the key of each entry to the predicate.
     #[inline]
-    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    pub fn retain_keys<F>(&mut self, mut f: F)
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        F: FnMut(&K) -> bool,
     {
-        self.raw_entry().from_key(k)
+        self.retain(|k, _| f(k));
     }
 
-    #[inline]
-    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    /// Like `LinkedHashMap::retain`, but returns the number of entries that were removed.
+    pub fn retain_count<F>(&mut self, mut f: F) -> usize
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        F: FnMut(&K, &mut V) -> bool,
     {
-        self.get(k).is_some()
+        let mut removed = 0;
+        self.retain(|k, v| {
+            let keep = f(k, v);
+            if !keep {
+                removed += 1;
+            }
+            keep
+        });
+        removed
     }
 
-    #[inline]
-    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    /// Removes every entry whose value is the `same` as the value of the last entry kept before it,
+    /// walking from front to back, so that only the first entry of each run is kept.
+    ///
+    /// `same` is called with the value of the last kept entry and the value of the entry being
+    /// visited.  The remaining entries keep their order, and the nodes of removed entries are kept
+    /// for future insertions.
+    pub fn dedup_by_value<F>(&mut self, mut same: F)
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        F: FnMut(&V, &V) -> bool,
     {
-        match self.raw_entry_mut().from_key(k) {
-            RawEntryMut::Occupied(occupied) => Some(occupied.into_mut()),
-            RawEntryMut::Vacant(_) => None,
+        unsafe {
+            let values = match self.values {
+                Some(values) => values,
+                None => return,
+            };
+            let mut kept = values.as_ref().links.value.next;
+            if kept == values {
+                return;
+            }
+            let mut cur = kept.as_ref().links.value.next;
+            while cur != values {
+                let next = cur.as_ref().links.value.next;
+                if same(&kept.as_ref().entry_ref().1, &cur.as_ref().entry_ref().1) {
+                    match self.table.find_entry(hash_node(cur), |&n| n == cur) {
+                        Ok(entry) => {
+                            entry.remove();
+                        }
+                        Err(_) => unreachable!("node is not in the table"),
+                    }
+                    drop(remove_node(&mut self.free, cur));
+                } else {
+                    kept = cur;
+                }
+                cur = next;
+            }
         }
     }
 
-    /// Inserts the given key / value pair at the *back* of the internal linked list.
+    /// Moves every entry for which `pred` returns false into a new map, which is returned, and
+    /// keeps the entries for which it returns true.
     ///
-    /// Returns the previously set value, if one existed prior to this call.  After this call,
-    /// calling `LinkedHashMap::back` will return a reference to this key / value pair.
-    #[inline]
-    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
-        match self.raw_entry_mut().from_key(&k) {
-            RawEntryMut::Occupied(mut occupied) => {
-                occupied.to_back();
-                Some(occupied.replace_value(v))
-            }
-            RawEntryMut::Vacant(vacant) => {
-                vacant.insert(k, v);
-                None
+    /// Both maps preserve the relative order of their entries.  The entries are moved without
+    /// being rehashed or reallocated.
+    pub fn partition<F>(&mut self, mut pred: F) -> Self
+    where
+        S: Clone,
+        A: Clone,
+        F: FnMut(&K, &V) -> bool,
+    {
+        let mut other = self.clone_empty();
+        unsafe {
+            if let Some(values) = self.values {
+                let mut cur = values.as_ref().links.value.next;
+                while cur != values {
+                    let next = cur.as_ref().links.value.next;
+                    let (k, v) = cur.as_ref().entry_ref();
+                    if !pred(k, v) {
+                        self.move_node_to_back_of(cur, &mut other);
+                    }
+                    cur = next;
+                }
             }
         }
+        other
     }
 
-    /// If the given key is not in this map, inserts the key / value pair at the *back* of the
-    /// internal linked list and returns `None`, otherwise, replaces the existing value with the
-    /// given value *without* moving the entry in the internal linked list and returns the previous
-    /// value.
-    #[inline]
-    pub fn replace(&mut self, k: K, v: V) -> Option<V> {
-        match self.raw_entry_mut().from_key(&k) {
-            RawEntryMut::Occupied(mut occupied) => Some(occupied.replace_value(v)),
-            RawEntryMut::Vacant(vacant) => {
-                vacant.insert(k, v);
-                None
+    // Moves a node of this map to the back of `other`, keeping its stored hash.  `other` must use
+    // the same hasher as this map, and an allocator able to free this map's nodes.
+    unsafe fn move_node_to_back_of(&mut self, node: NonNull<Node<K, V>>, other: &mut Self) {
+        let hash = node.as_ref().hash;
+        match self.table.find_entry(hash, |&n| n == node) {
+            Ok(entry) => {
+                entry.remove();
             }
+            Err(_) => unreachable!("node is not in the table"),
         }
+        detach_node(node);
+
+        // Clones of an allocator can free each other's memory, so the node can be moved as is.
+        ensure_guard_node(&mut other.values, other.table.allocator());
+        attach_before(node, NonNull::new_unchecked(other.values.as_ptr()));
+        other
+            .table
+            .insert_unique(hash, node, move |&n| hash_node(n));
     }
 
     #[inline]
-    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Swaps the contents of this map with `other`, without allocating or moving any entries.
+    ///
+    /// This is the same as `mem::swap(self, other)`: the internal table, linked list and free list
+    /// are exchanged along with the hasher and allocator, so each map keeps hashing its entries
+    /// with the hasher they were inserted with.
+    #[inline]
+    pub fn swap_with(&mut self, other: &mut Self) {
+        mem::swap(self, other);
+    }
+
+    /// Creates a new, empty map with a clone of this map's hasher and allocator.
+    ///
+    /// No entries are copied and nothing is allocated.
+    #[inline]
+    pub fn clone_empty(&self) -> Self
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        S: Clone,
+        A: Clone,
     {
-        match self.raw_entry_mut().from_key(k) {
-            RawEntryMut::Occupied(occupied) => Some(occupied.remove()),
-            RawEntryMut::Vacant(_) => None,
-        }
+        Self::with_hasher_in(self.hash_builder.clone(), self.allocator().clone())
     }
 
     #[inline]
-    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    pub fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+
+    /// Allocates `additional` nodes up front and places them on the internal free list, so that
+    /// the next `additional` insertions do not need to allocate nodes.
+    ///
+    /// Combined with `LinkedHashMap::reserve`, this moves all of the allocation for those
+    /// insertions up front.
+    #[inline]
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        unsafe {
+            ensure_guard_node(&mut self.values, self.table.allocator());
+            for _ in 0..additional {
+                let node = new_node(self.table.allocator());
+                push_free(&mut self.free, node);
+            }
+        }
+    }
+
+    /// Deallocates nodes on the internal free list until at most `max_retained` free nodes remain.
+    ///
+    /// Unlike `LinkedHashMap::shrink_to_fit`, this does not shrink the internal table and lets the
+    /// caller choose how many nodes are kept around for future insertions.
+    #[inline]
+    pub fn shrink_free_list(&mut self, max_retained: usize) {
+        unsafe {
+            let mut free = &mut self.free;
+            for _ in 0..max_retained {
+                match *free {
+                    Some(node) => free = &mut (*node.as_ptr()).links.free.next,
+                    None => return,
+                }
+            }
+            drop_free_nodes(free.take(), self.table.allocator());
+        }
+    }
+
+    /// Returns mutable references to the values at each of the given positions in the internal
+    /// linked list.
+    ///
+    /// Returns `None` if any index is out of bounds or if any two indices are equal.  Each index is
+    /// found by walking the list, so this takes O(N * n) time.
+    pub fn get_disjoint_indices_mut<const N: usize>(
+        &mut self,
+        indices: [usize; N],
+    ) -> Option<[&mut V; N]> {
+        for (i, index) in indices.iter().enumerate() {
+            if *index >= self.len() || indices[..i].contains(index) {
+                return None;
+            }
+        }
+
+        let mut nodes = [NonNull::dangling(); N];
+        for (node, &index) in nodes.iter_mut().zip(indices.iter()) {
+            *node = self.nth_node(index)?;
+        }
+        // All nodes are distinct, so the returned references do not alias.
+        Some(nodes.map(|node| unsafe { &mut (*node.as_ptr()).entry_mut().1 }))
+    }
+
+    /// Returns an occupied entry for the entry at the front of the internal linked list, or `None`
+    /// if the map is empty.
+    ///
+    /// The returned entry has no key of its own, so `OccupiedEntry::replace_key`,
+    /// `OccupiedEntry::replace_entry` and `OccupiedEntry::insert_entry` panic on it.  Use
+    /// `OccupiedEntry::replace_key_with` or `OccupiedEntry::replace_entry_with_key` instead.
+    #[inline]
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V, S, A>> {
+        if self.is_empty() {
+            return None;
+        }
+        let front = unsafe { (*self.values.as_ptr()).links.value.next };
+        Some(self.node_entry(front))
+    }
+
+    /// Returns an occupied entry for the entry at the back of the internal linked list, or `None`
+    /// if the map is empty.
+    ///
+    /// The returned entry has no key of its own, so `OccupiedEntry::replace_key`,
+    /// `OccupiedEntry::replace_entry` and `OccupiedEntry::insert_entry` panic on it.  Use
+    /// `OccupiedEntry::replace_key_with` or `OccupiedEntry::replace_entry_with_key` instead.
+    #[inline]
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V, S, A>> {
+        if self.is_empty() {
+            return None;
+        }
+        let back = unsafe { (*self.values.as_ptr()).links.value.prev };
+        Some(self.node_entry(back))
+    }
+
+    // Returns an occupied entry for the given node, which must be a value node of this map.
+    #[inline]
+    fn node_entry(&mut self, node: NonNull<Node<K, V>>) -> OccupiedEntry<'_, K, V, S, A> {
+        let hash = unsafe { node.as_ref().hash };
+        match self.table.find_entry(hash, |&n| n == node) {
+            Ok(entry) => OccupiedEntry {
+                key: None,
+                raw_entry: RawOccupiedEntryMut {
+                    hash_builder: &self.hash_builder,
+                    free: &mut self.free,
+                    values: &mut self.values,
+                    entry,
+                },
+                promote: true,
+            },
+            Err(_) => unreachable!("node is not in the table"),
+        }
+    }
+
+    // Returns the node at the given position in the internal linked list, walking from whichever
+    // end of the list is closer.
+    #[inline]
+    fn nth_node(&self, index: usize) -> Option<NonNull<Node<K, V>>> {
+        let len = self.len();
+        if index >= len {
+            return None;
+        }
+        unsafe {
+            let values = self.values.as_ptr();
+            if index < len / 2 {
+                let mut cur = (*values).links.value.next;
+                for _ in 0..index {
+                    cur = cur.as_ref().links.value.next;
+                }
+                Some(cur)
+            } else {
+                let mut cur = (*values).links.value.prev;
+                for _ in index + 1..len {
+                    cur = cur.as_ref().links.value.prev;
+                }
+                Some(cur)
+            }
+        }
+    }
+}
+
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S, A> {
+        self.entry_with_promote(key, true)
+    }
+
+    /// Like `LinkedHashMap::entry`, but the returned entry never moves an existing entry in the
+    /// internal linked list, unless `OccupiedEntry::to_back` or `OccupiedEntry::to_front` is
+    /// called explicitly.
+    ///
+    /// With `LinkedHashMap::entry`, the `Entry::or_insert` family of methods, `Entry::insert` and
+    /// `OccupiedEntry::insert` move an occupied entry to the back, like `LinkedHashMap::insert`.
+    /// With this method they leave it in place, like `LinkedHashMap::replace`.  Other methods,
+    /// such as `Entry::and_modify` or `OccupiedEntry::get_mut`, never move the entry, and vacant
+    /// entries are always inserted at the back.
+    #[inline]
+    pub fn entry_no_promote(&mut self, key: K) -> Entry<'_, K, V, S, A> {
+        self.entry_with_promote(key, false)
+    }
+
+    #[inline]
+    fn entry_with_promote(&mut self, key: K, promote: bool) -> Entry<'_, K, V, S, A> {
+        match self.raw_entry_mut().from_key(&key) {
+            RawEntryMut::Occupied(occupied) => Entry::Occupied(OccupiedEntry {
+                key: Some(key),
+                raw_entry: occupied,
+                promote,
+            }),
+            RawEntryMut::Vacant(vacant) => Entry::Vacant(VacantEntry {
+                key,
+                raw_entry: vacant,
+                promote,
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.raw_entry().from_key(k).map(|(_, v)| v)
+    }
+
+    /// Similar to `LinkedHashMap::get`, but looks up the key using hashbrown's `Equivalent` trait
+    /// rather than `Borrow`.
+    ///
+    /// This allows looking up keys by a type which cannot be expressed through `Borrow`, such as
+    /// looking up a `(String, String)` key with a pair of `&str`.
+    #[inline]
+    pub fn get_equiv<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = hash_key(&self.hash_builder, k);
+        self.raw_entry()
+            .from_hash(hash, |o| k.equivalent(o))
+            .map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.raw_entry().from_key(k)
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(k).is_some()
+    }
+
+    /// Like `LinkedHashMap::get`, but uses a precomputed `hash` for the key instead of hashing it.
+    ///
+    /// The caller is responsible for computing `hash` with this map's own `BuildHasher`, otherwise
+    /// the key will not be found.
+    #[inline]
+    pub fn get_hashed_nocheck<Q>(&self, hash: u64, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.raw_entry()
+            .from_key_hashed_nocheck(hash, k)
+            .map(|(_, v)| v)
+    }
+
+    /// Like `LinkedHashMap::contains_key`, but uses a precomputed `hash` for the key, see
+    /// `LinkedHashMap::get_hashed_nocheck`.
+    #[inline]
+    pub fn contains_key_hashed_nocheck<Q>(&self, hash: u64, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_hashed_nocheck(hash, k).is_some()
+    }
+
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.raw_entry_mut().from_key(k) {
+            RawEntryMut::Occupied(occupied) => Some(occupied.into_mut()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    /// Returns mutable references to both the stored key and the value for the given key.
+    ///
+    /// It is a logic error to modify the key in a way that changes its hash or its equality with
+    /// other keys while it is in the map.
+    #[inline]
+    pub fn get_key_value_mut<Q>(&mut self, k: &Q) -> Option<(&mut K, &mut V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.raw_entry_mut().from_key(k) {
+            RawEntryMut::Occupied(occupied) => Some(occupied.into_key_value()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    /// Similar to `LinkedHashMap::get_mut`, but looks up the key using the `Equivalent` trait rather
+    /// than `Borrow`.
+    #[inline]
+    pub fn get_mut_equiv<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = hash_key(&self.hash_builder, k);
+        match self.raw_entry_mut().from_hash(hash, |o| k.equivalent(o)) {
+            RawEntryMut::Occupied(occupied) => Some(occupied.into_mut()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    /// Inserts the given key / value pair at the *back* of the internal linked list.
+    ///
+    /// Returns the previously set value, if one existed prior to this call.  After this call,
+    /// calling `LinkedHashMap::back` will return a reference to this key / value pair.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        match self.raw_entry_mut().from_key(&k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                occupied.to_back();
+                Some(occupied.replace_value(v))
+            }
+            RawEntryMut::Vacant(vacant) => {
+                vacant.insert(k, v);
+                None
+            }
+        }
+    }
+
+    /// Inserts the given key / value pair at the *back* of the internal linked list, without
+    /// checking whether the key is already present.
+    ///
+    /// The caller must make sure the key is not already in the map.  Violating this does not cause
+    /// undefined behavior, but leaves the map holding duplicate entries for the key, and which of
+    /// them is found by a lookup is unspecified.
+    #[inline]
+    pub fn insert_unique_unchecked(&mut self, k: K, v: V) -> (&K, &mut V) {
+        let hash = hash_key(&self.hash_builder, &k);
+        let (k, v) = RawVacantEntryMut {
+            hash_builder: &self.hash_builder,
+            values: &mut self.values,
+            free: &mut self.free,
+            table: &mut self.table,
+        }
+        .insert_hashed_nocheck(hash, k, v);
+        (k, v)
+    }
+
+    /// Inserts the given key / value pair at the *front* of the internal linked list.
+    ///
+    /// This is the mirror image of `LinkedHashMap::insert`: returns the previously set value, if
+    /// one existed prior to this call, and afterwards calling `LinkedHashMap::front` will return a
+    /// reference to this key / value pair.
+    #[inline]
+    pub fn push_front(&mut self, k: K, v: V) -> Option<V> {
+        match self.raw_entry_mut().from_key(&k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                occupied.to_front();
+                Some(occupied.replace_value(v))
+            }
+            RawEntryMut::Vacant(vacant) => {
+                vacant.insert_front(k, v);
+                None
+            }
+        }
+    }
+
+    /// If the given key is not in this map, inserts the key / value pair at the *back* of the
+    /// internal linked list and returns `None`, otherwise, replaces the existing value with the
+    /// given value *without* moving the entry in the internal linked list and returns the previous
+    /// value.
+    #[inline]
+    pub fn replace(&mut self, k: K, v: V) -> Option<V> {
+        match self.raw_entry_mut().from_key(&k) {
+            RawEntryMut::Occupied(mut occupied) => Some(occupied.replace_value(v)),
+            RawEntryMut::Vacant(vacant) => {
+                vacant.insert(k, v);
+                None
+            }
+        }
+    }
+
+    /// Returns the value for the given key, moving its entry to the *back* of the internal linked
+    /// list, or inserts the value returned by `f(&key)` at the back if the key is not present.
+    ///
+    /// `f` is only called if the key is not present.  This is the same as
+    /// `entry(key).or_insert_with_key(f)`.
+    #[inline]
+    pub fn get_or_insert_with_key<F>(&mut self, key: K, f: F) -> &mut V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        match self.raw_entry_mut().from_key::<K>(&key) {
+            RawEntryMut::Occupied(mut occupied) => {
+                occupied.to_back();
+                occupied.into_mut()
+            }
+            RawEntryMut::Vacant(vacant) => {
+                let value = f(&key);
+                vacant.insert(key, value).1
+            }
+        }
+    }
+
+    /// Inserts the given key / value pair immediately *before* the entry for `pivot` in the
+    /// internal linked list.
+    ///
+    /// If the key is already present, its value is replaced and the entry is moved before `pivot`,
+    /// and the previous value is returned.  If `pivot` is not present, the map is left unchanged
+    /// and the key / value pair is returned as an error.
+    #[inline]
+    pub fn insert_before<Q>(&mut self, pivot: &Q, k: K, v: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.insert_relative(pivot, k, v, false)
+    }
+
+    /// Inserts the given key / value pair immediately *after* the entry for `pivot` in the internal
+    /// linked list.
+    ///
+    /// Behaves like `LinkedHashMap::insert_before` otherwise.
+    #[inline]
+    pub fn insert_after<Q>(&mut self, pivot: &Q, k: K, v: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.insert_relative(pivot, k, v, true)
+    }
+
+    fn insert_relative<Q>(
+        &mut self,
+        pivot: &Q,
+        k: K,
+        v: V,
+        after: bool,
+    ) -> Result<Option<V>, (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pivot = match self.find_node(pivot) {
+            Some(pivot) => pivot,
+            None => return Err((k, v)),
+        };
+
+        let (node, old) = match self.raw_entry_mut().from_key::<K>(&k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                (*occupied.entry.get(), Some(occupied.replace_value(v)))
+            }
+            RawEntryMut::Vacant(vacant) => (*vacant.insert_entry(k, v).entry.get(), None),
+        };
+
+        if node != pivot {
+            unsafe {
+                detach_node(node);
+                if after {
+                    attach_before(node, pivot.as_ref().links.value.next);
+                } else {
+                    attach_before(node, pivot);
+                }
+            }
+        }
+        Ok(old)
+    }
+
+    /// Moves all entries of `other` to the *back* of this map in order, leaving `other` empty.
+    ///
+    /// Keys already present in this map are handled as by `LinkedHashMap::insert`: the value is
+    /// replaced and the entry is moved to the back.  Since both maps are borrowed mutably, `other`
+    /// can never be this same map.
+    #[inline]
+    pub fn append(&mut self, other: &mut Self) {
+        self.extend(other.drain());
+    }
+
+    /// Inserts every item of `iter` at the *front* of the internal linked list, so that afterwards
+    /// the map starts with the items in the order `iter` produced them, followed by the entries
+    /// that were already present.
+    ///
+    /// Keys already present in this map have their value replaced and are moved into place, as
+    /// with `LinkedHashMap::push_front`.
+    pub fn extend_front<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        let mut prev: Option<NonNull<Node<K, V>>> = None;
+        for (k, v) in iter {
+            let node = match self.raw_entry_mut().from_key::<K>(&k) {
+                RawEntryMut::Occupied(mut occupied) => {
+                    occupied.replace_value(v);
+                    *occupied.entry.get()
+                }
+                RawEntryMut::Vacant(vacant) => *vacant.insert_entry(k, v).entry.get(),
+            };
+
+            unsafe {
+                let next = match prev {
+                    Some(prev) => prev.as_ref().links.value.next,
+                    None => self.values.unwrap().as_ref().links.value.next,
+                };
+                if prev != Some(node) && next != node {
+                    detach_node(node);
+                    attach_before(node, next);
+                }
+            }
+            prev = Some(node);
+        }
+    }
+
+    /// Builds a map from entries whose keys are known to be unique, in the order `iter` produces
+    /// them, without checking whether each key is already present.
+    ///
+    /// This inserts every entry with `LinkedHashMap::insert_unique_unchecked`, skipping the lookup
+    /// that `FromIterator` performs for every entry, which is useful when the entries come from a
+    /// source that is already a map, such as a serialized `LinkedHashMap` or `BTreeMap`.
+    ///
+    /// If `iter` does produce duplicate keys, this does not cause undefined behavior, but it is a
+    /// logic error: the map will hold an entry for each duplicate and which of them is found by a
+    /// lookup is unspecified.
+    pub fn from_sorted_iter_unchecked<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self
+    where
+        S: Default,
+        A: Default,
+    {
+        let iter = iter.into_iter();
+        let mut map =
+            Self::with_capacity_and_hasher_in(iter.size_hint().0, S::default(), A::default());
+        for (k, v) in iter {
+            map.insert_unique_unchecked(k, v);
+        }
+        map
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.raw_entry_mut().from_key(k) {
+            RawEntryMut::Occupied(occupied) => Some(occupied.remove()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    /// Removes the entry for the given key, returning the stored key along with its value.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
@@ -377,7 +1283,7 @@ where
         }
         unsafe {
             let front = (*self.values.as_ptr()).links.value.next;
-            let hash = hash_node(&self.hash_builder, front);
+            let hash = front.as_ref().hash;
             match self
                 .raw_entry_mut()
                 .from_hash(hash, |k| k.eq(front.as_ref().key_ref()))
@@ -388,6 +1294,24 @@ where
         }
     }
 
+    /// Returns an iterator that removes and yields entries from the front of the internal linked
+    /// list for as long as `pred` returns true.
+    ///
+    /// The first entry for which `pred` returns false is left in place and ends the iteration.
+    /// Entries are only removed as they are yielded, so dropping the iterator early leaves the
+    /// remaining entries in the map.
+    #[inline]
+    pub fn pop_front_while<F>(&mut self, pred: F) -> PopFrontWhile<'_, K, V, S, F, A>
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        PopFrontWhile {
+            map: self,
+            pred,
+            done: false,
+        }
+    }
+
     #[inline]
     pub fn pop_back(&mut self) -> Option<(K, V)> {
         if self.is_empty() {
@@ -395,7 +1319,7 @@ where
         }
         unsafe {
             let back = (*self.values.as_ptr()).links.value.prev;
-            let hash = hash_node(&self.hash_builder, back);
+            let hash = back.as_ref().hash;
             match self
                 .raw_entry_mut()
                 .from_hash(hash, |k| k.eq(back.as_ref().key_ref()))
@@ -442,16 +1366,35 @@ where
 
     #[inline]
     pub fn reserve(&mut self, additional: usize) {
-        let hash_builder = &self.hash_builder;
         self.table
-            .reserve(additional, move |&n| unsafe { hash_node(hash_builder, n) });
+            .reserve(additional, move |&n| unsafe { hash_node(n) });
+    }
+
+    /// Reserves capacity for at least `additional` more entries in the internal table, and makes
+    /// sure at least `additional` nodes are available on the internal free list.
+    ///
+    /// After this call, the next `additional` insertions will not allocate.  Nodes already on the
+    /// free list count towards `additional`, so only the missing nodes are allocated.
+    pub fn reserve_full(&mut self, additional: usize) {
+        self.reserve(additional);
+        let mut free_nodes = 0;
+        unsafe {
+            let mut free = self.free;
+            while free_nodes < additional {
+                match free {
+                    Some(node) => free = (*node.as_ptr()).links.free.next,
+                    None => break,
+                }
+                free_nodes += 1;
+            }
+        }
+        self.reserve_nodes(additional - free_nodes);
     }
 
     #[inline]
     pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        let hash_builder = &self.hash_builder;
         self.table
-            .try_reserve(additional, move |&n| unsafe { hash_node(hash_builder, n) })
+            .try_reserve(additional, move |&n| unsafe { hash_node(n) })
             .map_err(|e| match e {
                 hashbrown::TryReserveError::CapacityOverflow => TryReserveError::CapacityOverflow,
                 hashbrown::TryReserveError::AllocError { layout } => {
@@ -460,87 +1403,483 @@ where
             })
     }
 
-    #[inline]
-    pub fn shrink_to_fit(&mut self) {
-        let hash_builder = &self.hash_builder;
-        unsafe {
-            self.table
-                .shrink_to_fit(move |&n| hash_node(hash_builder, n));
-            drop_free_nodes(self.free.take());
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to_fit_retaining(0);
+    }
+
+    /// Shrinks the internal table as much as possible, like `LinkedHashMap::shrink_to_fit`, but
+    /// keeps up to `retain_free_nodes` nodes on the internal free list for future insertions.
+    #[inline]
+    pub fn shrink_to_fit_retaining(&mut self, retain_free_nodes: usize) {
+        unsafe {
+            self.table.shrink_to_fit(move |&n| hash_node(n));
+        }
+        self.shrink_free_list(retain_free_nodes);
+    }
+
+    pub fn retain_with_order<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let free = self.free;
+        let mut drop_filtered_values = DropFilteredValues {
+            free: &mut self.free,
+            cur_free: free,
+        };
+
+        if let Some(values) = self.values {
+            unsafe {
+                let mut cur = values.as_ref().links.value.next;
+                while cur != values {
+                    let next = cur.as_ref().links.value.next;
+                    let filter = {
+                        let (k, v) = (*cur.as_ptr()).entry_mut();
+                        !f(k, v)
+                    };
+                    if filter {
+                        self.table
+                            .find_entry(hash_node(cur), |&o| o == cur)
+                            .unwrap()
+                            .remove();
+                        drop_filtered_values.drop_later(cur);
+                    }
+                    cur = next;
+                }
+            }
+        }
+    }
+
+    /// Like `LinkedHashMap::retain_with_order`, but `f` may stop the scan early.
+    ///
+    /// Returning `ControlFlow::Continue(true)` keeps the entry and `ControlFlow::Continue(false)`
+    /// removes it.  Returning `ControlFlow::Break(())` keeps the entry and every entry after it
+    /// without visiting them, which avoids walking the whole list when the entries are ordered by
+    /// the filter criterion, e.g. expiring the oldest entries of a time-ordered map.
+    pub fn retain_while<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> ControlFlow<(), bool>,
+    {
+        let free = self.free;
+        let mut drop_filtered_values = DropFilteredValues {
+            free: &mut self.free,
+            cur_free: free,
+        };
+
+        if let Some(values) = self.values {
+            unsafe {
+                let mut cur = values.as_ref().links.value.next;
+                while cur != values {
+                    let next = cur.as_ref().links.value.next;
+                    let keep = {
+                        let (k, v) = (*cur.as_ptr()).entry_mut();
+                        match f(k, v) {
+                            ControlFlow::Continue(keep) => keep,
+                            ControlFlow::Break(()) => break,
+                        }
+                    };
+                    if !keep {
+                        self.table
+                            .find_entry(hash_node(cur), |&o| o == cur)
+                            .unwrap()
+                            .remove();
+                        drop_filtered_values.drop_later(cur);
+                    }
+                    cur = next;
+                }
+            }
+        }
+    }
+
+    /// Like `LinkedHashMap::retain_with_order`, but returns the removed entries in the order they
+    /// were removed, i.e. from front to back.
+    pub fn retain_returning<F>(&mut self, mut f: F) -> Vec<(K, V)>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut removed = Vec::new();
+        if let Some(values) = self.values {
+            unsafe {
+                let mut cur = values.as_ref().links.value.next;
+                while cur != values {
+                    let next = cur.as_ref().links.value.next;
+                    let keep = {
+                        let (k, v) = (*cur.as_ptr()).entry_mut();
+                        f(k, v)
+                    };
+                    if !keep {
+                        self.table
+                            .find_entry(hash_node(cur), |&o| o == cur)
+                            .unwrap()
+                            .remove();
+                        removed.push(remove_node(&mut self.free, cur));
+                    }
+                    cur = next;
+                }
+            }
+        }
+        removed
+    }
+
+    /// Returns an iterator that removes and yields every entry for which `pred` returns true.
+    ///
+    /// `pred` is called on each entry strictly in order from front to back, and the remaining
+    /// entries keep their order.  If the iterator is dropped before it is exhausted, the rest of the
+    /// entries are still visited and the matching ones removed, unlike `Vec::extract_if` which
+    /// keeps unvisited elements.  The nodes of removed entries are kept for future insertions, so
+    /// the map's capacity is unchanged.
+    #[inline]
+    pub fn drain_filter<F>(&mut self, pred: F) -> DrainFilter<'_, K, V, S, F, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let cur = self
+            .values
+            .map(|values| unsafe { values.as_ref().links.value.next });
+        DrainFilter {
+            map: self,
+            pred,
+            cur,
+            panicked: false,
+        }
+    }
+
+    /// Returns an iterator over the entries of the map starting at the entry with the given key and
+    /// continuing to the back of the internal linked list, or `None` if the key is not present.
+    ///
+    /// This must walk from the given entry to the back of the list to count the remaining entries,
+    /// so it is O(n) in the number of entries after the given key.
+    pub fn iter_from<Q>(&self, k: &Q) -> Option<Iter<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.find_node(k)?;
+
+        unsafe {
+            let values = self.values.as_ptr();
+            let mut remaining = 0;
+            let mut cur = node.as_ptr();
+            while cur != values {
+                remaining += 1;
+                cur = (*cur).links.value.next.as_ptr();
+            }
+
+            Some(Iter {
+                head: node.as_ptr(),
+                tail: (*values).links.value.prev.as_ptr(),
+                remaining,
+                marker: PhantomData,
+            })
+        }
+    }
+
+    /// Swaps the positions of the entries with the given keys in the internal linked list.
+    ///
+    /// Returns false and does nothing if either key is not present or if both keys refer to the
+    /// same entry.
+    pub fn swap_keys<Q>(&mut self, a: &Q, b: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (a, b) = match (self.find_node(a), self.find_node(b)) {
+            (Some(a), Some(b)) if a != b => (a, b),
+            _ => return false,
+        };
+
+        unsafe {
+            let a_next = a.as_ref().links.value.next;
+            if a_next == b {
+                detach_node(b);
+                attach_before(b, a);
+            } else {
+                detach_node(a);
+                attach_before(a, b);
+                detach_node(b);
+                attach_before(b, a_next);
+            }
+        }
+        true
+    }
+
+    /// Returns true if both maps contain the same keys mapped to equal values, ignoring the order
+    /// of their entries.
+    ///
+    /// This is the equality of `std::collections::HashMap`, as opposed to the order-sensitive
+    /// `PartialEq` implementation.
+    pub fn eq_unordered(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+
+    /// Rotates the internal linked list so that the entry with the given key becomes the front,
+    /// preserving the cyclic order of all entries.
+    ///
+    /// Entries that came before the given key are moved, in order, to the back of the list.
+    /// Returns false if the key is not present.
+    pub fn rotate_to_front<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find_node(k) {
+            Some(node) => {
+                unsafe {
+                    let guard = NonNull::new_unchecked(self.values.as_ptr());
+                    detach_node(guard);
+                    attach_before(guard, node);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves every entry after the entry for the given key into a new map, which is returned,
+    /// keeping the given key and the entries before it.
+    ///
+    /// Both maps preserve the order of their entries.  Returns `None` if the key is not present,
+    /// and an empty map if it is the last key.
+    pub fn split_off_after<Q>(&mut self, k: &Q) -> Option<Self>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: Clone,
+        A: Clone,
+    {
+        let node = self.find_node(k)?;
+        let mut other = self.clone_empty();
+        unsafe {
+            let values = NonNull::new_unchecked(self.values.as_ptr());
+            let mut cur = node.as_ref().links.value.next;
+            while cur != values {
+                let next = cur.as_ref().links.value.next;
+                self.move_node_to_back_of(cur, &mut other);
+                cur = next;
+            }
+        }
+        Some(other)
+    }
+
+    /// Checks the consistency of the internal linked list and table, panicking if it is broken.
+    ///
+    /// This verifies that the list links are consistent in both directions, that the list and the
+    /// table hold the same number of entries, and that every node in the list is in the table
+    /// under the hash of its key.  It takes O(n) time, and is meant for tests and fuzzing.
+    ///
+    /// This is only available with debug assertions enabled and is not part of the stable API.
+    #[cfg(debug_assertions)]
+    #[doc(hidden)]
+    pub fn check_invariants(&self) {
+        let values = match self.values {
+            Some(values) => values,
+            None => {
+                assert_eq!(self.table.len(), 0, "entries without a guard node");
+                return;
+            }
+        };
+        unsafe {
+            let mut len = 0;
+            let mut cur = values;
+            loop {
+                let next = cur.as_ref().links.value.next;
+                assert!(
+                    next.as_ref().links.value.prev == cur,
+                    "inconsistent links at position {}",
+                    len
+                );
+                if next == values {
+                    break;
+                }
+                let hash = hash_key(&self.hash_builder, next.as_ref().key_ref());
+                assert_eq!(next.as_ref().hash, hash, "stale hash at position {}", len);
+                assert!(
+                    self.table.find(hash, |&n| n == next).is_some(),
+                    "node at position {} is missing from the table",
+                    len
+                );
+                len += 1;
+                assert!(len <= self.table.len(), "list is longer than the table");
+                cur = next;
+            }
+            assert_eq!(len, self.table.len(), "list is shorter than the table");
+        }
+    }
+
+    #[inline]
+    fn find_node<Q>(&self, k: &Q) -> Option<NonNull<Node<K, V>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = hash_key(&self.hash_builder, k);
+        self.table
+            .find(hash, |o| unsafe { k.eq((*o).as_ref().key_ref().borrow()) })
+            .copied()
+    }
+
+    /// Similar to `LinkedHashMap::retain_with_order`, but also passes each entry's position in the
+    /// internal linked list to the predicate.
+    ///
+    /// The position is the 0-based index the entry had *before* any entries were removed, so every
+    /// entry visited receives a distinct index, in order.
+    pub fn retain_with_index<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &K, &mut V) -> bool,
+    {
+        let mut index = 0;
+        self.retain_with_order(|k, v| {
+            let keep = f(index, k, v);
+            index += 1;
+            keep
+        });
+    }
+
+    /// Creates a map from an iterator of key / value pairs, keeping the *first* occurrence of each
+    /// key.
+    ///
+    /// Unlike `collect`, where a later duplicate replaces the value and moves the entry to the
+    /// back, later duplicates here are ignored, so every entry keeps the value and position of the
+    /// first time its key was seen.
+    pub fn from_iter_first_wins<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        S: Default,
+        A: Default,
+    {
+        let iter = iter.into_iter();
+        let mut map =
+            Self::with_capacity_and_hasher_in(iter.size_hint().0, S::default(), A::default());
+        for (k, v) in iter {
+            if let RawEntryMut::Vacant(vacant) = map.raw_entry_mut().from_key(&k) {
+                vacant.insert(k, v);
+            }
         }
+        map
     }
 
-    pub fn retain_with_order<F>(&mut self, mut f: F)
+    /// Returns a new map with the same keys in the same order, with each value replaced by the
+    /// result of calling `f` on it.
+    pub fn map_values<W, F>(&self, mut f: F) -> LinkedHashMap<K, W, S, A>
     where
-        F: FnMut(&K, &mut V) -> bool,
+        K: Clone,
+        S: Clone,
+        A: Clone,
+        F: FnMut(&V) -> W,
     {
-        let free = self.free;
-        let mut drop_filtered_values = DropFilteredValues {
-            free: &mut self.free,
-            cur_free: free,
-        };
+        let mut map = LinkedHashMap::with_capacity_and_hasher_in(
+            self.len(),
+            self.hash_builder.clone(),
+            self.allocator().clone(),
+        );
+        for (k, v) in self.iter() {
+            map.insert(k.clone(), f(v));
+        }
+        map
+    }
 
-        if let Some(values) = self.values {
-            unsafe {
-                let mut cur = values.as_ref().links.value.next;
-                while cur != values {
-                    let next = cur.as_ref().links.value.next;
-                    let filter = {
-                        let (k, v) = (*cur.as_ptr()).entry_mut();
-                        !f(k, v)
-                    };
-                    if filter {
-                        let k = (*cur.as_ptr()).key_ref();
-                        let hash = hash_key(&self.hash_builder, k);
-                        self.table
-                            .find_entry(hash, |o| (*o).as_ref().key_ref().eq(k))
-                            .unwrap()
-                            .remove();
-                        drop_filtered_values.drop_later(cur);
-                    }
-                    cur = next;
-                }
-            }
+    /// Consuming version of `LinkedHashMap::map_values`, which moves the keys and values out of
+    /// this map rather than cloning the keys.
+    pub fn into_map_values<W, F>(mut self, mut f: F) -> LinkedHashMap<K, W, S, A>
+    where
+        S: Clone,
+        A: Clone,
+        F: FnMut(V) -> W,
+    {
+        let mut map = LinkedHashMap::with_capacity_and_hasher_in(
+            self.len(),
+            self.hash_builder.clone(),
+            self.allocator().clone(),
+        );
+        for (k, v) in self.drain() {
+            map.insert(k, f(v));
         }
+        map
     }
 }
 
-impl<K, V, S> LinkedHashMap<K, V, S>
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A>
 where
     S: BuildHasher,
 {
     #[inline]
-    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S> {
+    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S, A> {
         RawEntryBuilder { map: self }
     }
 
     #[inline]
-    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S> {
+    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S, A> {
         RawEntryBuilderMut { map: self }
     }
 }
 
-impl<K, V, S> Default for LinkedHashMap<K, V, S>
+impl<K, V, S, A> Default for LinkedHashMap<K, V, S, A>
 where
     S: Default,
+    A: Allocator + Default,
 {
     #[inline]
     fn default() -> Self {
-        Self::with_hasher(S::default())
+        Self::with_hasher_in(S::default(), A::default())
     }
 }
 
-impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for LinkedHashMap<K, V, S> {
+impl<K: Hash + Eq, V, S: BuildHasher + Default, A: Allocator + Default> FromIterator<(K, V)>
+    for LinkedHashMap<K, V, S, A>
+{
     #[inline]
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
         let iter = iter.into_iter();
-        let mut map = Self::with_capacity_and_hasher(iter.size_hint().0, S::default());
+        let mut map =
+            Self::with_capacity_and_hasher_in(iter.size_hint().0, S::default(), A::default());
         map.extend(iter);
         map
     }
 }
 
-impl<K, V, S> fmt::Debug for LinkedHashMap<K, V, S>
+/// Converts a `std::collections::HashMap` into a `LinkedHashMap` using a clone of its hasher.
+///
+/// The order of the resulting map is the (unspecified) iteration order of the source `HashMap`,
+/// and is stable from then on.
+#[cfg(feature = "std")]
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> From<std::collections::HashMap<K, V, S>>
+    for LinkedHashMap<K, V, S>
+{
+    #[inline]
+    fn from(map: std::collections::HashMap<K, V, S>) -> Self {
+        let mut linked = Self::with_capacity_and_hasher(map.len(), map.hasher().clone());
+        linked.extend(map);
+        linked
+    }
+}
+
+/// The entries of the resulting `HashMap` are no longer ordered.  It uses a clone of the map's
+/// hasher.
+#[cfg(feature = "std")]
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> From<LinkedHashMap<K, V, S>>
+    for std::collections::HashMap<K, V, S>
+{
+    #[inline]
+    fn from(linked: LinkedHashMap<K, V, S>) -> Self {
+        let mut map = Self::with_capacity_and_hasher(linked.len(), linked.hasher().clone());
+        map.extend(linked);
+        map
+    }
+}
+
+/// The entries of the resulting `Vec` are in order from front to back, see
+/// `LinkedHashMap::into_vec`.
+impl<K, V, S, A: Allocator> From<LinkedHashMap<K, V, S, A>> for Vec<(K, V)> {
+    #[inline]
+    fn from(linked: LinkedHashMap<K, V, S, A>) -> Self {
+        linked.into_vec()
+    }
+}
+
+impl<K, V, S, A: Allocator> fmt::Debug for LinkedHashMap<K, V, S, A>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -551,17 +1890,21 @@ where
     }
 }
 
-impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for LinkedHashMap<K, V, S> {
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher, A: Allocator> PartialEq
+    for LinkedHashMap<K, V, S, A>
+{
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
 }
 
-impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for LinkedHashMap<K, V, S> {}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher, A: Allocator> Eq for LinkedHashMap<K, V, S, A> {}
 
-impl<K: Hash + Eq + PartialOrd, V: PartialOrd, S: BuildHasher> PartialOrd
-    for LinkedHashMap<K, V, S>
+/// Maps are compared lexicographically by their entries in iteration order, the same way a
+/// `Vec<(K, V)>` of those entries would be compared.
+impl<K: Hash + Eq + PartialOrd, V: PartialOrd, S: BuildHasher, A: Allocator> PartialOrd
+    for LinkedHashMap<K, V, S, A>
 {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -589,14 +1932,15 @@ impl<K: Hash + Eq + PartialOrd, V: PartialOrd, S: BuildHasher> PartialOrd
     }
 }
 
-impl<K: Hash + Eq + Ord, V: Ord, S: BuildHasher> Ord for LinkedHashMap<K, V, S> {
+/// Like `PartialOrd`, entries are compared lexicographically in iteration order.
+impl<K: Hash + Eq + Ord, V: Ord, S: BuildHasher, A: Allocator> Ord for LinkedHashMap<K, V, S, A> {
     #[inline]
     fn cmp(&self, other: &Self) -> Ordering {
         self.iter().cmp(other)
     }
 }
 
-impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for LinkedHashMap<K, V, S> {
+impl<K: Hash + Eq, V: Hash, S: BuildHasher, A: Allocator> Hash for LinkedHashMap<K, V, S, A> {
     #[inline]
     fn hash<H: Hasher>(&self, h: &mut H) {
         for e in self.iter() {
@@ -605,23 +1949,24 @@ impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for LinkedHashMap<K, V, S> {
     }
 }
 
-impl<K, V, S> Drop for LinkedHashMap<K, V, S> {
+impl<K, V, S, A: Allocator> Drop for LinkedHashMap<K, V, S, A> {
     #[inline]
     fn drop(&mut self) {
         unsafe {
+            let alloc = self.table.allocator();
             if let Some(values) = self.values {
-                drop_value_nodes(values);
-                let _ = Box::from_raw(values.as_ptr());
+                drop_value_nodes(values, alloc);
+                deallocate_node(values, alloc);
             }
-            drop_free_nodes(self.free);
+            drop_free_nodes(self.free, alloc);
         }
     }
 }
 
-unsafe impl<K: Send, V: Send, S: Send> Send for LinkedHashMap<K, V, S> {}
-unsafe impl<K: Sync, V: Sync, S: Sync> Sync for LinkedHashMap<K, V, S> {}
+unsafe impl<K: Send, V: Send, S: Send, A: Allocator + Send> Send for LinkedHashMap<K, V, S, A> {}
+unsafe impl<K: Sync, V: Sync, S: Sync, A: Allocator + Sync> Sync for LinkedHashMap<K, V, S, A> {}
 
-impl<'a, K, V, S, Q> Index<&'a Q> for LinkedHashMap<K, V, S>
+impl<'a, K, V, S, A: Allocator, Q> Index<&'a Q> for LinkedHashMap<K, V, S, A>
 where
     K: Hash + Eq + Borrow<Q>,
     S: BuildHasher,
@@ -635,7 +1980,7 @@ where
     }
 }
 
-impl<'a, K, V, S, Q> IndexMut<&'a Q> for LinkedHashMap<K, V, S>
+impl<'a, K, V, S, A: Allocator, Q> IndexMut<&'a Q> for LinkedHashMap<K, V, S, A>
 where
     K: Hash + Eq + Borrow<Q>,
     S: BuildHasher,
@@ -647,25 +1992,69 @@ where
     }
 }
 
-impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> Clone for LinkedHashMap<K, V, S> {
+/// Returns the value at the given position in the internal linked list, counted from the front.
+///
+/// This walks the list from the closer end, so it takes O(n) time.
+///
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+impl<K, V, S, A: Allocator> Index<usize> for LinkedHashMap<K, V, S, A> {
+    type Output = V;
+
+    #[inline]
+    fn index(&self, index: usize) -> &V {
+        match self.nth_node(index) {
+            Some(node) => unsafe { &(*node.as_ptr()).entry_ref().1 },
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len(),
+                index
+            ),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone, A: Allocator + Clone> Clone
+    for LinkedHashMap<K, V, S, A>
+{
+    /// The clone's internal table is allocated up front with room for every entry of `self`, so
+    /// cloning never re-hashes.  The clone does not inherit `self`'s free list.
     #[inline]
     fn clone(&self) -> Self {
-        let mut map = Self::with_hasher(self.hash_builder.clone());
+        let mut map = Self::with_capacity_and_hasher_in(
+            self.len(),
+            self.hash_builder.clone(),
+            self.allocator().clone(),
+        );
         map.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
         map
     }
+
+    /// Reuses the nodes of `self`'s existing entries for the cloned entries, so that cloning into
+    /// a map that already has at least as many entries as `source` does not allocate any nodes.
+    ///
+    /// `self` keeps its own allocator.
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        self.clear_retaining_capacity();
+        self.hash_builder.clone_from(&source.hash_builder);
+        self.extend(source.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
 }
 
-impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for LinkedHashMap<K, V, S> {
+impl<K: Hash + Eq, V, S: BuildHasher, A: Allocator> Extend<(K, V)> for LinkedHashMap<K, V, S, A> {
     #[inline]
     fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
         for (k, v) in iter {
             self.insert(k, v);
         }
     }
 }
 
-impl<'a, K, V, S> Extend<(&'a K, &'a V)> for LinkedHashMap<K, V, S>
+impl<'a, K, V, S, A: Allocator> Extend<(&'a K, &'a V)> for LinkedHashMap<K, V, S, A>
 where
     K: 'a + Hash + Eq + Copy,
     V: 'a + Copy,
@@ -673,18 +2062,20 @@ where
 {
     #[inline]
     fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
         for (&k, &v) in iter {
             self.insert(k, v);
         }
     }
 }
 
-pub enum Entry<'a, K, V, S> {
-    Occupied(OccupiedEntry<'a, K, V>),
-    Vacant(VacantEntry<'a, K, V, S>),
+pub enum Entry<'a, K, V, S, A: Allocator = Global> {
+    Occupied(OccupiedEntry<'a, K, V, S, A>),
+    Vacant(VacantEntry<'a, K, V, S, A>),
 }
 
-impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for Entry<'_, K, V, S> {
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug for Entry<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -694,12 +2085,39 @@ impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for Entry<'_, K, V, S> {
     }
 }
 
-impl<'a, K, V, S> Entry<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> Entry<'a, K, V, S, A> {
+    /// Sets the value of this entry and returns the now occupied entry.
+    ///
+    /// If this entry is vacant, the new entry is inserted at the *back* of the internal linked
+    /// list.  If it is occupied, the value is replaced and, similarly to `OccupiedEntry::insert`,
+    /// the existing entry is moved to the back, unless this entry was returned from
+    /// `LinkedHashMap::entry_no_promote`.
+    ///
+    /// If this entry was vacant, the returned entry has no key of its own, so
+    /// `OccupiedEntry::replace_key`, `OccupiedEntry::replace_entry` and
+    /// `OccupiedEntry::insert_entry` panic on it.  Use `OccupiedEntry::replace_key_with` or
+    /// `OccupiedEntry::replace_entry_with_key` instead.
+    #[inline]
+    pub fn insert(self, value: V) -> OccupiedEntry<'a, K, V, S, A>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+
     /// If this entry is vacant, inserts a new entry with the given value and returns a reference to
     /// it.
     ///
     /// If this entry is occupied, this method *moves the occupied entry to the back of the internal
-    /// linked list* and returns a reference to the existing value.
+    /// linked list* and returns a reference to the existing value, unless this entry was returned
+    /// from `LinkedHashMap::entry_no_promote`.
     #[inline]
     pub fn or_insert(self, default: V) -> &'a mut V
     where
@@ -708,13 +2126,33 @@ impl<'a, K, V, S> Entry<'a, K, V, S> {
     {
         match self {
             Entry::Occupied(mut entry) => {
-                entry.to_back();
+                entry.promote();
                 entry.into_mut()
             }
             Entry::Vacant(entry) => entry.insert(default),
         }
     }
 
+    /// Similar to `Entry::or_insert`, but also returns true if the entry was vacant and the given
+    /// value was inserted.
+    ///
+    /// Like `Entry::or_insert`, an occupied entry is moved to the back of the internal linked list,
+    /// unless this entry was returned from `LinkedHashMap::entry_no_promote`.
+    #[inline]
+    pub fn or_insert_checked(self, default: V) -> (&'a mut V, bool)
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.promote();
+                (entry.into_mut(), false)
+            }
+            Entry::Vacant(entry) => (entry.insert(default), true),
+        }
+    }
+
     /// Similar to `Entry::or_insert`, but accepts a function to construct a new value if this entry
     /// is vacant.
     #[inline]
@@ -725,13 +2163,44 @@ impl<'a, K, V, S> Entry<'a, K, V, S> {
     {
         match self {
             Entry::Occupied(mut entry) => {
-                entry.to_back();
+                entry.promote();
                 entry.into_mut()
             }
             Entry::Vacant(entry) => entry.insert(default()),
         }
     }
 
+    /// Similar to `Entry::or_insert_with`, but the function to construct a new value is passed a
+    /// reference to this entry's key.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.promote();
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Similar to `Entry::or_insert`, but inserts `V::default()` if this entry is vacant.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        K: Hash,
+        V: Default,
+        S: BuildHasher,
+    {
+        self.or_insert_with(V::default)
+    }
+
     #[inline]
     pub fn key(&self) -> &K {
         match *self {
@@ -740,6 +2209,18 @@ impl<'a, K, V, S> Entry<'a, K, V, S> {
         }
     }
 
+    /// Returns a mutable reference to the key that will be inserted if this entry is vacant, or
+    /// `None` if it is occupied.
+    ///
+    /// See `VacantEntry::key_mut`.
+    #[inline]
+    pub fn key_mut(&mut self) -> Option<&mut K> {
+        match *self {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(ref mut entry) => Some(entry.key_mut()),
+        }
+    }
+
     #[inline]
     pub fn and_modify<F>(self, f: F) -> Self
     where
@@ -753,14 +2234,30 @@ impl<'a, K, V, S> Entry<'a, K, V, S> {
             Entry::Vacant(entry) => Entry::Vacant(entry),
         }
     }
+
+    /// If this entry is occupied, calls `OccupiedEntry::replace_entry_with` with the given
+    /// function, otherwise returns the vacant entry unchanged.
+    #[inline]
+    pub fn and_replace_entry_with<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.replace_entry_with(f),
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
 }
 
-pub struct OccupiedEntry<'a, K, V> {
-    key: K,
-    raw_entry: RawOccupiedEntryMut<'a, K, V>,
+pub struct OccupiedEntry<'a, K, V, S, A: Allocator = Global> {
+    key: Option<K>,
+    raw_entry: RawOccupiedEntryMut<'a, K, V, S, A>,
+    // Whether inserting through this entry moves it to the back, false for entries returned from
+    // `LinkedHashMap::entry_no_promote`.
+    promote: bool,
 }
 
-impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OccupiedEntry<'_, K, V> {
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug for OccupiedEntry<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("OccupiedEntry")
@@ -770,12 +2267,20 @@ impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OccupiedEntry<'_, K, V> {
     }
 }
 
-impl<'a, K, V> OccupiedEntry<'a, K, V> {
+impl<'a, K, V, S, A: Allocator> OccupiedEntry<'a, K, V, S, A> {
     #[inline]
     pub fn key(&self) -> &K {
         self.raw_entry.key()
     }
 
+    /// Returns a mutable reference to the key stored in the map.
+    ///
+    /// The key must not be modified in a way that changes its hash or its equality with other keys.
+    #[inline]
+    pub fn key_mut(&mut self) -> &mut K {
+        self.raw_entry.key_mut()
+    }
+
     #[inline]
     pub fn remove_entry(self) -> (K, V) {
         self.raw_entry.remove_entry()
@@ -806,16 +2311,31 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
         self.raw_entry.to_front()
     }
 
+    /// Moves this entry so that it is at position `index` in the internal linked list, see
+    /// `RawOccupiedEntryMut::move_to_index`.
+    #[inline]
+    pub fn move_to_index(&mut self, index: usize) {
+        self.raw_entry.move_to_index(index)
+    }
+
     /// Replaces this entry's value with the provided value.
     ///
     /// Similarly to `LinkedHashMap::insert`, this moves the existing entry to the back of the
-    /// internal linked list.
+    /// internal linked list, unless this entry was returned from
+    /// `LinkedHashMap::entry_no_promote`.
     #[inline]
     pub fn insert(&mut self, value: V) -> V {
-        self.raw_entry.to_back();
+        self.promote();
         self.raw_entry.replace_value(value)
     }
 
+    #[inline]
+    fn promote(&mut self) {
+        if self.promote {
+            self.raw_entry.to_back();
+        }
+    }
+
     #[inline]
     pub fn remove(self) -> V {
         self.raw_entry.remove()
@@ -823,6 +2343,12 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
 
     /// Similar to `OccupiedEntry::replace_entry`, but *does* move the entry to the back of the
     /// internal linked list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this entry was not returned from `LinkedHashMap::entry`, as entries returned from
+    /// e.g. `Entry::insert` or `LinkedHashMap::first_entry` have no key to replace with.  Use
+    /// `OccupiedEntry::replace_entry_with_key` for those.
     #[inline]
     pub fn insert_entry(mut self, value: V) -> (K, V) {
         self.raw_entry.to_back();
@@ -833,8 +2359,17 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
     /// entry's value with the given `value` parameter.
     ///
     /// Does *not* move the entry to the back of the internal linked list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this entry was not returned from `LinkedHashMap::entry`, as entries returned from
+    /// e.g. `Entry::insert` or `LinkedHashMap::first_entry` have no key to replace with.  Use
+    /// `OccupiedEntry::replace_entry_with_key` for those.
     pub fn replace_entry(mut self, value: V) -> (K, V) {
-        let old_key = mem::replace(self.raw_entry.key_mut(), self.key);
+        let key = self.key.take().expect("no key to replace with");
+        // Neither replacement drops anything, the old key and value are only dropped by the caller
+        // once the entry is fully consistent again.
+        let old_key = mem::replace(self.raw_entry.key_mut(), key);
         let old_value = mem::replace(self.raw_entry.get_mut(), value);
         (old_key, old_value)
     }
@@ -842,30 +2377,94 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
     /// Replaces this entry's key with the key provided to `LinkedHashMap::entry`.
     ///
     /// Does *not* move the entry to the back of the internal linked list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this entry was not returned from `LinkedHashMap::entry`, as entries returned from
+    /// e.g. `Entry::insert` or `LinkedHashMap::first_entry` have no key to replace with.  Use
+    /// `OccupiedEntry::replace_key_with` for those.
     #[inline]
     pub fn replace_key(mut self) -> K {
-        mem::replace(self.raw_entry.key_mut(), self.key)
+        let key = self.key.take().expect("no key to replace with");
+        mem::replace(self.raw_entry.key_mut(), key)
+    }
+
+    /// Replaces this entry's key with the given key, which must be equal to the existing key and
+    /// hash to the same value.
+    ///
+    /// Unlike `OccupiedEntry::replace_key`, this never panics, so it works on every occupied entry,
+    /// including ones returned from `Entry::insert` or `LinkedHashMap::first_entry`.  Does *not*
+    /// move the entry to the back of the internal linked list.
+    #[inline]
+    pub fn replace_key_with(mut self, key: K) -> K {
+        self.raw_entry.replace_key(key)
+    }
+
+    /// Replaces this entry's key and value with the given key and value, returning the old ones.
+    ///
+    /// The given key must be equal to the existing key and hash to the same value.  Unlike
+    /// `OccupiedEntry::replace_entry`, this never panics.  Does *not* move the entry to the back
+    /// of the internal linked list.
+    #[inline]
+    pub fn replace_entry_with_key(mut self, key: K, value: V) -> (K, V) {
+        let old_key = self.raw_entry.replace_key(key);
+        let old_value = self.raw_entry.replace_value(value);
+        (old_key, old_value)
+    }
+
+    /// Calls the given function with this entry's key and its value moved out of the map.
+    ///
+    /// If the function returns `Some`, the returned value is put back in place of the old one
+    /// *without* moving the entry in the internal linked list and an occupied entry is returned.
+    /// If the function returns `None`, the entry is removed and a vacant entry is returned instead.
+    #[inline]
+    pub fn replace_entry_with<F>(self, f: F) -> Entry<'a, K, V, S, A>
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        match self.raw_entry.replace_value_with(f) {
+            Ok(raw_entry) => Entry::Occupied(OccupiedEntry {
+                key: self.key,
+                raw_entry,
+                promote: self.promote,
+            }),
+            Err((key, raw_entry)) => Entry::Vacant(VacantEntry {
+                key,
+                raw_entry,
+                promote: self.promote,
+            }),
+        }
     }
 }
 
-pub struct VacantEntry<'a, K, V, S> {
+pub struct VacantEntry<'a, K, V, S, A: Allocator = Global> {
     key: K,
-    raw_entry: RawVacantEntryMut<'a, K, V, S>,
+    raw_entry: RawVacantEntryMut<'a, K, V, S, A>,
+    promote: bool,
 }
 
-impl<K: fmt::Debug, V, S> fmt::Debug for VacantEntry<'_, K, V, S> {
+impl<K: fmt::Debug, V, S, A: Allocator> fmt::Debug for VacantEntry<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("VacantEntry").field(self.key()).finish()
     }
 }
 
-impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> VacantEntry<'a, K, V, S, A> {
     #[inline]
     pub fn key(&self) -> &K {
         &self.key
     }
 
+    /// Returns a mutable reference to the key that will be inserted, e.g. to canonicalize it.
+    ///
+    /// The key is re-hashed on insertion, but it must not be changed to equal a key which is
+    /// already present in the map, or the map will contain duplicate keys.
+    #[inline]
+    pub fn key_mut(&mut self) -> &mut K {
+        &mut self.key
+    }
+
     #[inline]
     pub fn into_key(self) -> K {
         self.key
@@ -881,13 +2480,32 @@ impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
     {
         self.raw_entry.insert(self.key, value).1
     }
+
+    /// Similar to `VacantEntry::insert`, but returns the newly occupied entry rather than just a
+    /// reference to the value.
+    ///
+    /// The returned entry has no key of its own, so `OccupiedEntry::replace_key`,
+    /// `OccupiedEntry::replace_entry` and `OccupiedEntry::insert_entry` panic on it.  Use
+    /// `OccupiedEntry::replace_key_with` or `OccupiedEntry::replace_entry_with_key` instead.
+    #[inline]
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S, A>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        OccupiedEntry {
+            key: None,
+            raw_entry: self.raw_entry.insert_entry(self.key, value),
+            promote: self.promote,
+        }
+    }
 }
 
-pub struct RawEntryBuilder<'a, K, V, S> {
-    map: &'a LinkedHashMap<K, V, S>,
+pub struct RawEntryBuilder<'a, K, V, S, A: Allocator = Global> {
+    map: &'a LinkedHashMap<K, V, S, A>,
 }
 
-impl<'a, K, V, S> RawEntryBuilder<'a, K, V, S>
+impl<'a, K, V, S, A: Allocator> RawEntryBuilder<'a, K, V, S, A>
 where
     S: BuildHasher,
 {
@@ -928,32 +2546,34 @@ where
     }
 }
 
-unsafe impl<'a, K, V, S> Send for RawEntryBuilder<'a, K, V, S>
+unsafe impl<'a, K, V, S, A: Allocator> Send for RawEntryBuilder<'a, K, V, S, A>
 where
     K: Send,
     V: Send,
     S: Send,
+    A: Send,
 {
 }
 
-unsafe impl<'a, K, V, S> Sync for RawEntryBuilder<'a, K, V, S>
+unsafe impl<'a, K, V, S, A: Allocator> Sync for RawEntryBuilder<'a, K, V, S, A>
 where
     K: Sync,
     V: Sync,
     S: Sync,
+    A: Sync,
 {
 }
 
-pub struct RawEntryBuilderMut<'a, K, V, S> {
-    map: &'a mut LinkedHashMap<K, V, S>,
+pub struct RawEntryBuilderMut<'a, K, V, S, A: Allocator = Global> {
+    map: &'a mut LinkedHashMap<K, V, S, A>,
 }
 
-impl<'a, K, V, S> RawEntryBuilderMut<'a, K, V, S>
+impl<'a, K, V, S, A: Allocator> RawEntryBuilderMut<'a, K, V, S, A>
 where
     S: BuildHasher,
 {
     #[inline]
-    pub fn from_key<Q>(self, k: &Q) -> RawEntryMut<'a, K, V, S>
+    pub fn from_key<Q>(self, k: &Q) -> RawEntryMut<'a, K, V, S, A>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
@@ -963,7 +2583,7 @@ where
     }
 
     #[inline]
-    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, k: &Q) -> RawEntryMut<'a, K, V, S>
+    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, k: &Q) -> RawEntryMut<'a, K, V, S, A>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
@@ -976,7 +2596,7 @@ where
         self,
         hash: u64,
         mut is_match: impl FnMut(&K) -> bool,
-    ) -> RawEntryMut<'a, K, V, S> {
+    ) -> RawEntryMut<'a, K, V, S, A> {
         let entry = self
             .map
             .table
@@ -984,6 +2604,7 @@ where
 
         match entry {
             Ok(occupied) => RawEntryMut::Occupied(RawOccupiedEntryMut {
+                hash_builder: &self.map.hash_builder,
                 free: &mut self.map.free,
                 values: &mut self.map.values,
                 entry: occupied,
@@ -992,34 +2613,36 @@ where
                 hash_builder: &self.map.hash_builder,
                 values: &mut self.map.values,
                 free: &mut self.map.free,
-                entry: absent,
+                table: absent.into_table(),
             }),
         }
     }
 }
 
-unsafe impl<'a, K, V, S> Send for RawEntryBuilderMut<'a, K, V, S>
+unsafe impl<'a, K, V, S, A: Allocator> Send for RawEntryBuilderMut<'a, K, V, S, A>
 where
     K: Send,
     V: Send,
     S: Send,
+    A: Send,
 {
 }
 
-unsafe impl<'a, K, V, S> Sync for RawEntryBuilderMut<'a, K, V, S>
+unsafe impl<'a, K, V, S, A: Allocator> Sync for RawEntryBuilderMut<'a, K, V, S, A>
 where
     K: Sync,
     V: Sync,
     S: Sync,
+    A: Sync,
 {
 }
 
-pub enum RawEntryMut<'a, K, V, S> {
-    Occupied(RawOccupiedEntryMut<'a, K, V>),
-    Vacant(RawVacantEntryMut<'a, K, V, S>),
+pub enum RawEntryMut<'a, K, V, S, A: Allocator = Global> {
+    Occupied(RawOccupiedEntryMut<'a, K, V, S, A>),
+    Vacant(RawVacantEntryMut<'a, K, V, S, A>),
 }
 
-impl<'a, K, V, S> RawEntryMut<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> RawEntryMut<'a, K, V, S, A> {
     /// Similarly to `Entry::or_insert`, if this entry is occupied, it will move the existing entry
     /// to the back of the internal linked list.
     #[inline]
@@ -1074,15 +2697,29 @@ impl<'a, K, V, S> RawEntryMut<'a, K, V, S> {
             RawEntryMut::Vacant(entry) => RawEntryMut::Vacant(entry),
         }
     }
+
+    /// If this entry is occupied, calls `RawOccupiedEntryMut::replace_entry_with` with the given
+    /// function, otherwise returns the vacant entry unchanged.
+    #[inline]
+    pub fn and_replace_entry_with<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        match self {
+            RawEntryMut::Occupied(entry) => entry.replace_entry_with(f),
+            RawEntryMut::Vacant(entry) => RawEntryMut::Vacant(entry),
+        }
+    }
 }
 
-pub struct RawOccupiedEntryMut<'a, K, V> {
+pub struct RawOccupiedEntryMut<'a, K, V, S, A: Allocator = Global> {
+    hash_builder: &'a S,
     free: &'a mut Option<NonNull<Node<K, V>>>,
     values: &'a mut Option<NonNull<Node<K, V>>>,
-    entry: hash_table::OccupiedEntry<'a, NonNull<Node<K, V>>>,
+    entry: hash_table::OccupiedEntry<'a, NonNull<Node<K, V>>, A>,
 }
 
-impl<'a, K, V> RawOccupiedEntryMut<'a, K, V> {
+impl<'a, K, V, S, A: Allocator> RawOccupiedEntryMut<'a, K, V, S, A> {
     #[inline]
     pub fn key(&self) -> &K {
         self.get_key_value().0
@@ -1140,6 +2777,52 @@ impl<'a, K, V> RawOccupiedEntryMut<'a, K, V> {
         }
     }
 
+    // Removes entries from the front of the internal linked list, skipping this entry, until the
+    // map holds at most `len` entries, then returns this entry's value.
+    #[inline]
+    pub(crate) fn shrink_front_into_mut(self, len: usize) -> &'a mut V {
+        unsafe {
+            let node = *self.entry.get();
+            let table = self.entry.into_table();
+            let values = NonNull::new_unchecked(self.values.as_ptr());
+            while table.len() > len {
+                let mut front = values.as_ref().links.value.next;
+                if front == node {
+                    front = node.as_ref().links.value.next;
+                }
+                if front == values {
+                    break;
+                }
+                match table.find_entry(hash_node(front), |&n| n == front) {
+                    Ok(entry) => {
+                        entry.remove();
+                    }
+                    Err(_) => unreachable!("node is not in the table"),
+                }
+                drop(remove_node(self.free, front));
+            }
+            &mut (*node.as_ptr()).entry_mut().1
+        }
+    }
+
+    /// Returns the 0-based position of this entry in the internal linked list, counted from the
+    /// front.
+    ///
+    /// This walks the list from this entry back to the front, so it takes O(n) time.
+    #[inline]
+    pub fn index(&self) -> usize {
+        unsafe {
+            let values = self.values.as_ptr();
+            let mut index = 0;
+            let mut cur = (*self.entry.get().as_ptr()).links.value.prev.as_ptr();
+            while cur != values {
+                index += 1;
+                cur = (*cur).links.value.prev.as_ptr();
+            }
+            index
+        }
+    }
+
     #[inline]
     pub fn to_back(&mut self) {
         unsafe {
@@ -1158,6 +2841,27 @@ impl<'a, K, V> RawOccupiedEntryMut<'a, K, V> {
         }
     }
 
+    /// Moves this entry so that it is at the 0-based position `index` in the internal linked list,
+    /// shifting the entries in between.  An `index` past the end moves the entry to the back.
+    ///
+    /// This walks the list from the front to find the new position, so it takes O(n) time.
+    #[inline]
+    pub fn move_to_index(&mut self, index: usize) {
+        unsafe {
+            let node = *self.entry.get_mut();
+            let values = NonNull::new_unchecked(self.values.as_ptr());
+            detach_node(node);
+            let mut at = values.as_ref().links.value.next;
+            for _ in 0..index {
+                if at == values {
+                    break;
+                }
+                at = at.as_ref().links.value.next;
+            }
+            attach_before(node, at);
+        }
+    }
+
     #[inline]
     pub fn replace_value(&mut self, value: V) -> V {
         unsafe {
@@ -1175,25 +2879,84 @@ impl<'a, K, V> RawOccupiedEntryMut<'a, K, V> {
     }
 
     #[inline]
-    pub fn remove(self) -> V {
-        self.remove_entry().1
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        let node = self.entry.remove().0;
+        unsafe { remove_node(self.free, node) }
+    }
+
+    /// Calls the given function with this entry's key and its value moved out of the map.
+    ///
+    /// If the function returns `Some`, the returned value is put back in place of the old one
+    /// *without* moving the entry in the internal linked list and the entry stays occupied.  If
+    /// the function returns `None`, the entry is removed and a vacant entry is returned instead.
+    #[inline]
+    pub fn replace_entry_with<F>(self, f: F) -> RawEntryMut<'a, K, V, S, A>
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        match self.replace_value_with(f) {
+            Ok(occupied) => RawEntryMut::Occupied(occupied),
+            Err((_, vacant)) => RawEntryMut::Vacant(vacant),
+        }
+    }
+
+    // Implementation of `replace_entry_with` that returns the removed key when the entry becomes
+    // vacant.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn replace_value_with<F>(self, f: F) -> Result<Self, (K, RawVacantEntryMut<'a, K, V, S, A>)>
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        unsafe {
+            let node = *self.entry.get();
+            let entry = (*node.as_ptr()).entry.as_mut_ptr();
+
+            let mut guard = RemoveKeyOnUnwind { entry: Some(self) };
+            let new_value = f(&(*entry).0, ptr::read(ptr::addr_of!((*entry).1)));
+            let this = guard.entry.take().unwrap();
+
+            match new_value {
+                Some(value) => {
+                    ptr::write(ptr::addr_of_mut!((*entry).1), value);
+                    Ok(this)
+                }
+                None => Err(this.remove_key_only()),
+            }
+        }
     }
 
+    // Removes this entry from the map and returns *only* its key, the value must have already been
+    // moved out of the node.
     #[inline]
-    pub fn remove_entry(self) -> (K, V) {
-        let node = self.entry.remove().0;
-        unsafe { remove_node(self.free, node) }
+    unsafe fn remove_key_only(self) -> (K, RawVacantEntryMut<'a, K, V, S, A>) {
+        let (node, vacant) = self.entry.remove();
+        detach_node(node);
+        push_free(self.free, node);
+        let key = ptr::read(ptr::addr_of!((*(*node.as_ptr()).entry.as_ptr()).0));
+        let vacant = RawVacantEntryMut {
+            hash_builder: self.hash_builder,
+            values: self.values,
+            free: self.free,
+            table: vacant.into_table(),
+        };
+        (key, vacant)
     }
 }
 
-pub struct RawVacantEntryMut<'a, K, V, S> {
+pub struct RawVacantEntryMut<'a, K, V, S, A: Allocator = Global> {
     hash_builder: &'a S,
     values: &'a mut Option<NonNull<Node<K, V>>>,
     free: &'a mut Option<NonNull<Node<K, V>>>,
-    entry: hash_table::AbsentEntry<'a, NonNull<Node<K, V>>>,
+    table: &'a mut HashTable<NonNull<Node<K, V>>, A>,
 }
 
-impl<'a, K, V, S> RawVacantEntryMut<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> RawVacantEntryMut<'a, K, V, S, A> {
     #[inline]
     pub fn insert(self, key: K, value: V) -> (&'a mut K, &'a mut V)
     where
@@ -1204,6 +2967,19 @@ impl<'a, K, V, S> RawVacantEntryMut<'a, K, V, S> {
         self.insert_hashed_nocheck(hash, key, value)
     }
 
+    /// Like `RawVacantEntryMut::insert`, but places the new entry at the *front* of the internal
+    /// linked list rather than at the back.
+    #[inline]
+    pub fn insert_front(self, key: K, value: V) -> (&'a mut K, &'a mut V)
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let mut entry = self.insert_entry(key, value);
+        entry.to_front();
+        entry.into_key_value()
+    }
+
     #[inline]
     pub fn insert_hashed_nocheck(self, hash: u64, key: K, value: V) -> (&'a mut K, &'a mut V)
     where
@@ -1225,32 +3001,58 @@ impl<'a, K, V, S> RawVacantEntryMut<'a, K, V, S> {
     where
         S: BuildHasher,
     {
+        self.insert_entry_with_hasher(hash, key, value, hasher)
+            .into_key_value()
+    }
+
+    #[inline]
+    pub(crate) fn insert_entry(self, key: K, value: V) -> RawOccupiedEntryMut<'a, K, V, S, A>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let hash_builder = self.hash_builder;
+        let hash = hash_key(hash_builder, &key);
+        self.insert_entry_with_hasher(hash, key, value, |k| hash_key(hash_builder, k))
+    }
+
+    #[inline]
+    fn insert_entry_with_hasher(
+        self,
+        hash: u64,
+        key: K,
+        value: V,
+        hasher: impl Fn(&K) -> u64,
+    ) -> RawOccupiedEntryMut<'a, K, V, S, A> {
         unsafe {
-            ensure_guard_node(self.values);
-            let mut new_node = allocate_node(self.free);
+            ensure_guard_node(self.values, self.table.allocator());
+            let mut new_node = allocate_node(self.free, self.table.allocator());
             new_node.as_mut().put_entry((key, value));
+            new_node.as_mut().hash = hash;
             attach_before(new_node, NonNull::new_unchecked(self.values.as_ptr()));
 
-            let node = self
-                .entry
-                .into_table()
-                .insert_unique(hash, new_node, move |k| hasher((*k).as_ref().key_ref()))
-                .into_mut();
+            let entry = self
+                .table
+                .insert_unique(hash, new_node, move |k| hasher((*k).as_ref().key_ref()));
 
-            let (key, value) = (*node.as_ptr()).entry_mut();
-            (key, value)
+            RawOccupiedEntryMut {
+                hash_builder: self.hash_builder,
+                free: self.free,
+                values: self.values,
+                entry,
+            }
         }
     }
 }
 
-impl<K, V, S> fmt::Debug for RawEntryBuilderMut<'_, K, V, S> {
+impl<K, V, S, A: Allocator> fmt::Debug for RawEntryBuilderMut<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawEntryBuilder").finish()
     }
 }
 
-impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawEntryMut<'_, K, V, S> {
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug for RawEntryMut<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -1260,7 +3062,9 @@ impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawEntryMut<'_, K, V, S> {
     }
 }
 
-impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RawOccupiedEntryMut<'_, K, V> {
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug
+    for RawOccupiedEntryMut<'_, K, V, S, A>
+{
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawOccupiedEntryMut")
@@ -1270,47 +3074,53 @@ impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RawOccupiedEntryMut<'_, K, V>
     }
 }
 
-impl<K, V, S> fmt::Debug for RawVacantEntryMut<'_, K, V, S> {
+impl<K, V, S, A: Allocator> fmt::Debug for RawVacantEntryMut<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawVacantEntryMut").finish()
     }
 }
 
-impl<K, V, S> fmt::Debug for RawEntryBuilder<'_, K, V, S> {
+impl<K, V, S, A: Allocator> fmt::Debug for RawEntryBuilder<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawEntryBuilder").finish()
     }
 }
 
-unsafe impl<'a, K, V> Send for RawOccupiedEntryMut<'a, K, V>
+unsafe impl<'a, K, V, S, A: Allocator> Send for RawOccupiedEntryMut<'a, K, V, S, A>
 where
     K: Send,
     V: Send,
+    S: Send,
+    A: Send,
 {
 }
 
-unsafe impl<'a, K, V> Sync for RawOccupiedEntryMut<'a, K, V>
+unsafe impl<'a, K, V, S, A: Allocator> Sync for RawOccupiedEntryMut<'a, K, V, S, A>
 where
     K: Sync,
     V: Sync,
+    S: Sync,
+    A: Sync,
 {
 }
 
-unsafe impl<'a, K, V, S> Send for RawVacantEntryMut<'a, K, V, S>
+unsafe impl<'a, K, V, S, A: Allocator> Send for RawVacantEntryMut<'a, K, V, S, A>
 where
     K: Send,
     V: Send,
     S: Send,
+    A: Send,
 {
 }
 
-unsafe impl<'a, K, V, S> Sync for RawVacantEntryMut<'a, K, V, S>
+unsafe impl<'a, K, V, S, A: Allocator> Sync for RawVacantEntryMut<'a, K, V, S, A>
 where
     K: Sync,
     V: Sync,
     S: Sync,
+    A: Sync,
 {
 }
 
@@ -1328,10 +3138,13 @@ pub struct IterMut<'a, K, V> {
     marker: PhantomData<(&'a K, &'a mut V)>,
 }
 
-pub struct IntoIter<K, V> {
+pub struct IntoIter<K, V, A: Allocator = Global> {
     head: Option<NonNull<Node<K, V>>>,
     tail: Option<NonNull<Node<K, V>>>,
     remaining: usize,
+    // The emptied table of the consumed map, kept only to own the allocator for the remaining
+    // nodes.
+    table: HashTable<NonNull<Node<K, V>>, A>,
     marker: PhantomData<(K, V)>,
 }
 
@@ -1345,8 +3158,10 @@ pub struct Drain<'a, K, V> {
 }
 
 impl<K, V> IterMut<'_, K, V> {
+    /// Returns a read-only iterator over the entries this iterator has not yet yielded, borrowing
+    /// from `self`.
     #[inline]
-    pub(crate) fn iter(&self) -> Iter<'_, K, V> {
+    pub fn as_iter(&self) -> Iter<'_, K, V> {
         Iter {
             head: self.head.as_ptr(),
             tail: self.tail.as_ptr(),
@@ -1356,7 +3171,7 @@ impl<K, V> IterMut<'_, K, V> {
     }
 }
 
-impl<K, V> IntoIter<K, V> {
+impl<K, V, A: Allocator> IntoIter<K, V, A> {
     #[inline]
     pub(crate) fn iter(&self) -> Iter<'_, K, V> {
         Iter {
@@ -1378,6 +3193,31 @@ impl<K, V> Drain<'_, K, V> {
             marker: PhantomData,
         }
     }
+
+    /// Returns the entry that the next call to `next` would yield, without advancing the iterator.
+    #[inline]
+    pub fn peek_front(&self) -> Option<(&K, &V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        unsafe {
+            let (key, value) = (*self.head.as_ptr()).entry_ref();
+            Some((key, value))
+        }
+    }
+
+    /// Returns the entry that the next call to `next_back` would yield, without advancing the
+    /// iterator.
+    #[inline]
+    pub fn peek_back(&self) -> Option<(&K, &V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        unsafe {
+            let (key, value) = (*self.tail.as_ptr()).entry_ref();
+            Some((key, value))
+        }
+    }
 }
 
 unsafe impl<'a, K, V> Send for Iter<'a, K, V>
@@ -1394,10 +3234,11 @@ where
 {
 }
 
-unsafe impl<K, V> Send for IntoIter<K, V>
+unsafe impl<K, V, A: Allocator> Send for IntoIter<K, V, A>
 where
     K: Send,
     V: Send,
+    A: Send,
 {
 }
 
@@ -1422,10 +3263,11 @@ where
 {
 }
 
-unsafe impl<K, V> Sync for IntoIter<K, V>
+unsafe impl<K, V, A: Allocator> Sync for IntoIter<K, V, A>
 where
     K: Sync,
     V: Sync,
+    A: Sync,
 {
 }
 
@@ -1443,6 +3285,30 @@ impl<'a, K, V> Clone for Iter<'a, K, V> {
     }
 }
 
+impl<K, V> Default for Iter<'_, K, V> {
+    #[inline]
+    fn default() -> Self {
+        Iter {
+            head: ptr::null(),
+            tail: ptr::null(),
+            remaining: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for IterMut<'_, K, V> {
+    #[inline]
+    fn default() -> Self {
+        IterMut {
+            head: None,
+            tail: None,
+            remaining: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
 impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1457,11 +3323,11 @@ where
 {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_list().entries(self.iter()).finish()
+        f.debug_list().entries(self.as_iter()).finish()
     }
 }
 
-impl<K, V> fmt::Debug for IntoIter<K, V>
+impl<K, V, A: Allocator> fmt::Debug for IntoIter<K, V, A>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -1504,6 +3370,21 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.remaining, Some(self.remaining))
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<(&'a K, &'a V)> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            return None;
+        }
+        self.remaining -= n;
+        unsafe {
+            for _ in 0..n {
+                self.head = (*self.head).links.value.next.as_ptr();
+            }
+        }
+        self.next()
+    }
 }
 
 impl<'a, K, V> Iterator for IterMut<'a, K, V> {
@@ -1528,9 +3409,24 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.remaining, Some(self.remaining))
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<(&'a K, &'a mut V)> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            return None;
+        }
+        self.remaining -= n;
+        unsafe {
+            for _ in 0..n {
+                self.head = Some((*self.head.as_ptr()).links.value.next);
+            }
+        }
+        self.next()
+    }
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
+impl<K, V, A: Allocator> Iterator for IntoIter<K, V, A> {
     type Item = (K, V);
 
     #[inline]
@@ -1542,8 +3438,9 @@ impl<K, V> Iterator for IntoIter<K, V> {
         unsafe {
             let head = self.head.as_ptr();
             self.head = Some((*head).links.value.next);
-            let mut e = Box::from_raw(head);
-            Some(e.take_entry())
+            let entry = (*head).take_entry();
+            deallocate_node(NonNull::new_unchecked(head), self.table.allocator());
+            Some(entry)
         }
     }
 
@@ -1592,6 +3489,21 @@ impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
             }
         }
     }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<(&'a K, &'a V)> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            return None;
+        }
+        self.remaining -= n;
+        unsafe {
+            for _ in 0..n {
+                self.tail = (*self.tail).links.value.prev.as_ptr();
+            }
+        }
+        self.next_back()
+    }
 }
 
 impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
@@ -1609,9 +3521,24 @@ impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
             }
         }
     }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<(&'a K, &'a mut V)> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            return None;
+        }
+        self.remaining -= n;
+        unsafe {
+            for _ in 0..n {
+                self.tail = Some((*self.tail.as_ptr()).links.value.prev);
+            }
+        }
+        self.next_back()
+    }
 }
 
-impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+impl<K, V, A: Allocator> DoubleEndedIterator for IntoIter<K, V, A> {
     #[inline]
     fn next_back(&mut self) -> Option<(K, V)> {
         if self.remaining == 0 {
@@ -1619,9 +3546,11 @@ impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
         }
         self.remaining -= 1;
         unsafe {
-            let mut e = *Box::from_raw(self.tail.as_ptr());
-            self.tail = Some(e.links.value.prev);
-            Some(e.take_entry())
+            let tail = self.tail.as_ptr();
+            self.tail = Some((*tail).links.value.prev);
+            let entry = (*tail).take_entry();
+            deallocate_node(NonNull::new_unchecked(tail), self.table.allocator());
+            Some(entry)
         }
     }
 }
@@ -1647,9 +3576,11 @@ impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
 
 impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
 
-impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V, A: Allocator> ExactSizeIterator for IntoIter<K, V, A> {}
+
+impl<'a, K, V> ExactSizeIterator for Drain<'a, K, V> {}
 
-impl<K, V> Drop for IntoIter<K, V> {
+impl<K, V, A: Allocator> Drop for IntoIter<K, V, A> {
     #[inline]
     fn drop(&mut self) {
         for _ in 0..self.remaining {
@@ -1657,7 +3588,7 @@ impl<K, V> Drop for IntoIter<K, V> {
                 let tail = self.tail.as_ptr();
                 self.tail = Some((*tail).links.value.prev);
                 (*tail).take_entry();
-                let _ = Box::from_raw(tail);
+                deallocate_node(NonNull::new_unchecked(tail), self.table.allocator());
             }
         }
     }
@@ -1697,6 +3628,15 @@ impl<'a, K, V> Clone for Keys<'a, K, V> {
     }
 }
 
+impl<K, V> Default for Keys<'_, K, V> {
+    #[inline]
+    fn default() -> Self {
+        Keys {
+            inner: Iter::default(),
+        }
+    }
+}
+
 impl<'a, K, V> Iterator for Keys<'a, K, V> {
     type Item = &'a K;
 
@@ -1709,6 +3649,11 @@ impl<'a, K, V> Iterator for Keys<'a, K, V> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.inner.size_hint()
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<&'a K> {
+        self.inner.nth(n).map(|e| e.0)
+    }
 }
 
 impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V> {
@@ -1716,6 +3661,11 @@ impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V> {
     fn next_back(&mut self) -> Option<&'a K> {
         self.inner.next_back().map(|e| e.0)
     }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<&'a K> {
+        self.inner.nth_back(n).map(|e| e.0)
+    }
 }
 
 impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {
@@ -1745,6 +3695,15 @@ impl<K, V: fmt::Debug> fmt::Debug for Values<'_, K, V> {
     }
 }
 
+impl<K, V> Default for Values<'_, K, V> {
+    #[inline]
+    fn default() -> Self {
+        Values {
+            inner: Iter::default(),
+        }
+    }
+}
+
 impl<'a, K, V> Iterator for Values<'a, K, V> {
     type Item = &'a V;
 
@@ -1757,6 +3716,11 @@ impl<'a, K, V> Iterator for Values<'a, K, V> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.inner.size_hint()
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<&'a V> {
+        self.inner.nth(n).map(|e| e.1)
+    }
 }
 
 impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
@@ -1764,6 +3728,11 @@ impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
     fn next_back(&mut self) -> Option<&'a V> {
         self.inner.next_back().map(|e| e.1)
     }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<&'a V> {
+        self.inner.nth_back(n).map(|e| e.1)
+    }
 }
 
 impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {
@@ -1784,7 +3753,16 @@ where
 {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_list().entries(self.inner.iter()).finish()
+        f.debug_list().entries(self.inner.as_iter()).finish()
+    }
+}
+
+impl<K, V> Default for ValuesMut<'_, K, V> {
+    #[inline]
+    fn default() -> Self {
+        ValuesMut {
+            inner: IterMut::default(),
+        }
     }
 }
 
@@ -1800,6 +3778,11 @@ impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.inner.size_hint()
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<&'a mut V> {
+        self.inner.nth(n).map(|e| e.1)
+    }
 }
 
 impl<'a, K, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
@@ -1807,6 +3790,11 @@ impl<'a, K, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
     fn next_back(&mut self) -> Option<&'a mut V> {
         self.inner.next_back().map(|e| e.1)
     }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<&'a mut V> {
+        self.inner.nth_back(n).map(|e| e.1)
+    }
 }
 
 impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {
@@ -1816,7 +3804,138 @@ impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {
     }
 }
 
-impl<'a, K, V, S> IntoIterator for &'a LinkedHashMap<K, V, S> {
+pub struct PopFrontWhile<'a, K, V, S, F, A: Allocator = Global> {
+    map: &'a mut LinkedHashMap<K, V, S, A>,
+    pred: F,
+    done: bool,
+}
+
+impl<'a, K, V, S, F, A> Iterator for PopFrontWhile<'a, K, V, S, F, A>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+    F: FnMut(&K, &V) -> bool,
+    A: Allocator,
+{
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        if self.done {
+            return None;
+        }
+        match self.map.front() {
+            Some((k, v)) if (self.pred)(k, v) => self.map.pop_front(),
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            (0, Some(self.map.len()))
+        }
+    }
+}
+
+impl<K, V, S, F, A: Allocator> fmt::Debug for PopFrontWhile<'_, K, V, S, F, A> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PopFrontWhile").finish()
+    }
+}
+
+pub struct DrainFilter<'a, K, V, S, F, A: Allocator = Global>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    map: &'a mut LinkedHashMap<K, V, S, A>,
+    pred: F,
+    // The next node to visit, or `None` once the guard node has been reached.
+    cur: Option<NonNull<Node<K, V>>>,
+    // Set while `pred` runs, so that we do not call it again while unwinding from a panic.
+    panicked: bool,
+}
+
+impl<K, V, S, F, A> Iterator for DrainFilter<'_, K, V, S, F, A>
+where
+    F: FnMut(&K, &mut V) -> bool,
+    A: Allocator,
+{
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let values = self.map.values?;
+            while let Some(node) = self.cur {
+                if node == values {
+                    self.cur = None;
+                    break;
+                }
+                self.cur = Some(node.as_ref().links.value.next);
+
+                self.panicked = true;
+                let remove = {
+                    let (k, v) = (*node.as_ptr()).entry_mut();
+                    (self.pred)(k, v)
+                };
+                self.panicked = false;
+
+                if remove {
+                    match self.map.table.find_entry(hash_node(node), |&n| n == node) {
+                        Ok(entry) => {
+                            entry.remove();
+                        }
+                        Err(_) => unreachable!("node is not in the table"),
+                    }
+                    return Some(remove_node(&mut self.map.free, node));
+                }
+            }
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.cur.is_none() {
+            (0, Some(0))
+        } else {
+            (0, Some(self.map.len()))
+        }
+    }
+}
+
+impl<K, V, S, F, A> Drop for DrainFilter<'_, K, V, S, F, A>
+where
+    F: FnMut(&K, &mut V) -> bool,
+    A: Allocator,
+{
+    #[inline]
+    fn drop(&mut self) {
+        if !self.panicked {
+            self.for_each(drop);
+        }
+    }
+}
+
+impl<K, V, S, F, A> fmt::Debug for DrainFilter<'_, K, V, S, F, A>
+where
+    F: FnMut(&K, &mut V) -> bool,
+    A: Allocator,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrainFilter").finish()
+    }
+}
+
+impl<'a, K, V, S, A: Allocator> IntoIterator for &'a LinkedHashMap<K, V, S, A> {
     type Item = (&'a K, &'a V);
     type IntoIter = Iter<'a, K, V>;
 
@@ -1826,7 +3945,7 @@ impl<'a, K, V, S> IntoIterator for &'a LinkedHashMap<K, V, S> {
     }
 }
 
-impl<'a, K, V, S> IntoIterator for &'a mut LinkedHashMap<K, V, S> {
+impl<'a, K, V, S, A: Allocator> IntoIterator for &'a mut LinkedHashMap<K, V, S, A> {
     type Item = (&'a K, &'a mut V);
     type IntoIter = IterMut<'a, K, V>;
 
@@ -1836,36 +3955,43 @@ impl<'a, K, V, S> IntoIterator for &'a mut LinkedHashMap<K, V, S> {
     }
 }
 
-impl<K, V, S> IntoIterator for LinkedHashMap<K, V, S> {
+impl<K, V, S, A: Allocator> IntoIterator for LinkedHashMap<K, V, S, A> {
     type Item = (K, V);
-    type IntoIter = IntoIter<K, V>;
+    type IntoIter = IntoIter<K, V, A>;
 
     #[inline]
-    fn into_iter(mut self) -> IntoIter<K, V> {
+    fn into_iter(self) -> IntoIter<K, V, A> {
+        // The table is moved into the iterator to keep the allocator alive, so the map itself must
+        // not be dropped.
+        let mut this = mem::ManuallyDrop::new(self);
         unsafe {
-            let (head, tail) = if let Some(values) = self.values {
+            let (head, tail) = if let Some(values) = this.values {
                 let ValueLinks {
                     next: head,
                     prev: tail,
                 } = values.as_ref().links.value;
 
-                let _ = Box::from_raw(self.values.as_ptr());
-                self.values = None;
+                deallocate_node(values, this.table.allocator());
+                this.values = None;
 
                 (Some(head), Some(tail))
             } else {
                 (None, None)
             };
-            let len = self.len();
+            let len = this.len();
 
-            drop_free_nodes(self.free.take());
+            drop_free_nodes(this.free.take(), this.table.allocator());
 
-            self.table.clear();
+            let mut table = ptr::read(&this.table);
+            table.clear();
+            table.shrink_to_fit(move |&n| hash_node(n));
+            ptr::drop_in_place(&mut this.hash_builder);
 
             IntoIter {
                 head,
                 tail,
                 remaining: len,
+                table,
                 marker: PhantomData,
             }
         }
@@ -1906,6 +4032,10 @@ union Links<K, V> {
 
 struct Node<K, V> {
     entry: MaybeUninit<(K, V)>,
+    // The hash of the key this node was inserted with, so that the key never needs to be re-hashed
+    // when resizing the table or finding the table entry for a node.  Meaningless for the guard
+    // node and for free nodes.
+    hash: u64,
     links: Links<K, V>,
 }
 
@@ -1950,19 +4080,39 @@ impl<T> OptNonNullExt<T> for Option<NonNull<T>> {
     }
 }
 
+// Allocate a new node with an uninitialized key / value and dangling links.
+#[inline]
+unsafe fn new_node<K, V, A: Allocator>(alloc: &A) -> NonNull<Node<K, V>> {
+    let layout = Layout::new::<Node<K, V>>();
+    let node = match alloc.allocate(layout) {
+        Ok(ptr) => ptr.cast::<Node<K, V>>(),
+        Err(_) => handle_alloc_error(layout),
+    };
+    node.as_ptr().write(Node {
+        entry: MaybeUninit::uninit(),
+        hash: 0,
+        links: Links {
+            value: ValueLinks {
+                next: NonNull::dangling(),
+                prev: NonNull::dangling(),
+            },
+        },
+    });
+    node
+}
+
+// Deallocate a node previously allocated with `new_node`.  The node's key / value must already be
+// uninitialized.
+#[inline]
+unsafe fn deallocate_node<K, V, A: Allocator>(node: NonNull<Node<K, V>>, alloc: &A) {
+    alloc.deallocate(node.cast(), Layout::new::<Node<K, V>>());
+}
+
 // Allocate a circular list guard node if not present.
 #[inline]
-unsafe fn ensure_guard_node<K, V>(head: &mut Option<NonNull<Node<K, V>>>) {
+unsafe fn ensure_guard_node<K, V, A: Allocator>(head: &mut Option<NonNull<Node<K, V>>>, alloc: &A) {
     if head.is_none() {
-        let mut p = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-            entry: MaybeUninit::uninit(),
-            links: Links {
-                value: ValueLinks {
-                    next: NonNull::dangling(),
-                    prev: NonNull::dangling(),
-                },
-            },
-        })));
+        let mut p = new_node(alloc);
         p.as_mut().links.value = ValueLinks { next: p, prev: p };
         *head = Some(p);
     }
@@ -2010,7 +4160,10 @@ unsafe fn pop_free<K, V>(
 }
 
 #[inline]
-unsafe fn allocate_node<K, V>(free_list: &mut Option<NonNull<Node<K, V>>>) -> NonNull<Node<K, V>> {
+unsafe fn allocate_node<K, V, A: Allocator>(
+    free_list: &mut Option<NonNull<Node<K, V>>>,
+    alloc: &A,
+) -> NonNull<Node<K, V>> {
     if let Some(mut free) = pop_free(free_list) {
         free.as_mut().links.value = ValueLinks {
             next: NonNull::dangling(),
@@ -2018,26 +4171,18 @@ unsafe fn allocate_node<K, V>(free_list: &mut Option<NonNull<Node<K, V>>>) -> No
         };
         free
     } else {
-        NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-            entry: MaybeUninit::uninit(),
-            links: Links {
-                value: ValueLinks {
-                    next: NonNull::dangling(),
-                    prev: NonNull::dangling(),
-                },
-            },
-        })))
+        new_node(alloc)
     }
 }
 
 // Given node is assumed to be the guard node and is *not* dropped.
 #[inline]
-unsafe fn drop_value_nodes<K, V>(guard: NonNull<Node<K, V>>) {
+unsafe fn drop_value_nodes<K, V, A: Allocator>(guard: NonNull<Node<K, V>>, alloc: &A) {
     let mut cur = guard.as_ref().links.value.prev;
     while cur != guard {
         let prev = cur.as_ref().links.value.prev;
         cur.as_mut().take_entry();
-        let _ = Box::from_raw(cur.as_ptr());
+        deallocate_node(cur, alloc);
         cur = prev;
     }
 }
@@ -2045,10 +4190,10 @@ unsafe fn drop_value_nodes<K, V>(guard: NonNull<Node<K, V>>) {
 // Drops all linked free nodes starting with the given node.  Free nodes are only non-circular
 // singly linked, and should have uninitialized keys / values.
 #[inline]
-unsafe fn drop_free_nodes<K, V>(mut free: Option<NonNull<Node<K, V>>>) {
+unsafe fn drop_free_nodes<K, V, A: Allocator>(mut free: Option<NonNull<Node<K, V>>>, alloc: &A) {
     while let Some(some_free) = free {
         let next_free = some_free.as_ref().links.free.next;
-        let _ = Box::from_raw(some_free.as_ptr());
+        deallocate_node(some_free, alloc);
         free = next_free;
     }
 }
@@ -2064,12 +4209,8 @@ unsafe fn remove_node<K, V>(
 }
 
 #[inline]
-unsafe fn hash_node<S, K, V>(s: &S, node: NonNull<Node<K, V>>) -> u64
-where
-    S: BuildHasher,
-    K: Hash,
-{
-    hash_key(s, node.as_ref().key_ref())
+unsafe fn hash_node<K, V>(node: NonNull<Node<K, V>>) -> u64 {
+    node.as_ref().hash
 }
 
 #[inline]
@@ -2118,3 +4259,20 @@ impl<'a, K, V> Drop for DropFilteredValues<'a, K, V> {
         }
     }
 }
+
+// Used while `RawOccupiedEntryMut::replace_entry_with` has moved the value out of its node.  If the
+// user provided function panics, the node is left with only an initialized key, so the entry must
+// be removed from the map and only the key dropped.
+struct RemoveKeyOnUnwind<'a, K, V, S, A: Allocator> {
+    entry: Option<RawOccupiedEntryMut<'a, K, V, S, A>>,
+}
+
+impl<'a, K, V, S, A: Allocator> Drop for RemoveKeyOnUnwind<'a, K, V, S, A> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            unsafe {
+                entry.remove_key_only();
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use core::{
     borrow::Borrow,
+    cmp::Ordering,
     fmt,
     hash::{BuildHasher, Hash, Hasher},
     iter::{Chain, FromIterator},
@@ -14,7 +15,7 @@ pub struct LinkedHashSet<T, S = DefaultHashBuilder> {
     map: LinkedHashMap<T, (), S>,
 }
 
-impl<T: Hash + Eq> LinkedHashSet<T, DefaultHashBuilder> {
+impl<T> LinkedHashSet<T, DefaultHashBuilder> {
     #[inline]
     pub fn new() -> LinkedHashSet<T, DefaultHashBuilder> {
         LinkedHashSet {
@@ -31,6 +32,20 @@ impl<T: Hash + Eq> LinkedHashSet<T, DefaultHashBuilder> {
 }
 
 impl<T, S> LinkedHashSet<T, S> {
+    #[inline]
+    pub fn with_hasher(hasher: S) -> LinkedHashSet<T, S> {
+        LinkedHashSet {
+            map: LinkedHashMap::with_hasher(hasher),
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> LinkedHashSet<T, S> {
+        LinkedHashSet {
+            map: LinkedHashMap::with_capacity_and_hasher(capacity, hasher),
+        }
+    }
+
     #[inline]
     pub fn capacity(&self) -> usize {
         self.map.capacity()
@@ -53,6 +68,7 @@ impl<T, S> LinkedHashSet<T, S> {
         self.map.is_empty()
     }
 
+    /// Removes all values from the set, returning them from front to back as an iterator.
     #[inline]
     pub fn drain(&mut self) -> Drain<T> {
         Drain {
@@ -65,6 +81,8 @@ impl<T, S> LinkedHashSet<T, S> {
         self.map.clear()
     }
 
+    /// Removes all values for which `f` returns false, preserving the order of the remaining
+    /// values.
     #[inline]
     pub fn retain<F>(&mut self, mut f: F)
     where
@@ -79,20 +97,6 @@ where
     T: Eq + Hash,
     S: BuildHasher,
 {
-    #[inline]
-    pub fn with_hasher(hasher: S) -> LinkedHashSet<T, S> {
-        LinkedHashSet {
-            map: LinkedHashMap::with_hasher(hasher),
-        }
-    }
-
-    #[inline]
-    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> LinkedHashSet<T, S> {
-        LinkedHashSet {
-            map: LinkedHashMap::with_capacity_and_hasher(capacity, hasher),
-        }
-    }
-
     #[inline]
     pub fn hasher(&self) -> &S {
         self.map.hasher()
@@ -113,6 +117,7 @@ where
         self.map.shrink_to_fit()
     }
 
+    /// Visits the values that are in `self` but not in `other`, in the order of `self`.
     #[inline]
     pub fn difference<'a>(&'a self, other: &'a LinkedHashSet<T, S>) -> Difference<'a, T, S> {
         Difference {
@@ -121,6 +126,10 @@ where
         }
     }
 
+    /// Visits the values that are in `self` or in `other` but not in both.
+    ///
+    /// The values of `self` that are not in `other` come first, in the order of `self`, followed by
+    /// the values of `other` that are not in `self`, in the order of `other`.
     #[inline]
     pub fn symmetric_difference<'a>(
         &'a self,
@@ -131,6 +140,7 @@ where
         }
     }
 
+    /// Visits the values that are in both `self` and `other`, in the order of `self`.
     #[inline]
     pub fn intersection<'a>(&'a self, other: &'a LinkedHashSet<T, S>) -> Intersection<'a, T, S> {
         Intersection {
@@ -139,6 +149,10 @@ where
         }
     }
 
+    /// Visits the values that are in `self` or in `other`, without duplicates.
+    ///
+    /// All values of `self` come first, in the order of `self`, followed by the values of `other`
+    /// that are not in `self`, in the order of `other`.
     #[inline]
     pub fn union<'a>(&'a self, other: &'a LinkedHashSet<T, S>) -> Union<'a, T, S> {
         Union {
@@ -187,16 +201,23 @@ where
             .0
     }
 
+    /// Returns true if `self` has no values in common with `other`.
     #[inline]
     pub fn is_disjoint(&self, other: &LinkedHashSet<T, S>) -> bool {
-        self.iter().all(|v| !other.contains(v))
+        if self.len() <= other.len() {
+            self.iter().all(|v| !other.contains(v))
+        } else {
+            other.iter().all(|v| !self.contains(v))
+        }
     }
 
+    /// Returns true if every value in `self` is also in `other`.
     #[inline]
     pub fn is_subset(&self, other: &LinkedHashSet<T, S>) -> bool {
-        self.iter().all(|v| other.contains(v))
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
     }
 
+    /// Returns true if every value in `other` is also in `self`.
     #[inline]
     pub fn is_superset(&self, other: &LinkedHashSet<T, S>) -> bool {
         other.is_subset(self)
@@ -236,6 +257,9 @@ where
         self.map.remove(value).is_some()
     }
 
+    /// Removes and returns the value in the set equal to the given one, if any.
+    ///
+    /// This returns the stored value itself rather than just whether it was present.
     #[inline]
     pub fn take<Q: ?Sized>(&mut self, value: &Q) -> Option<T>
     where
@@ -268,6 +292,8 @@ where
         self.map.pop_back().map(|(k, _)| k)
     }
 
+    /// Moves the given value to the *front* of the internal linked list, returning whether it was
+    /// present in the set.
     #[inline]
     pub fn to_front<Q: ?Sized>(&mut self, value: &Q) -> bool
     where
@@ -283,6 +309,8 @@ where
         }
     }
 
+    /// Moves the given value to the *back* of the internal linked list, returning whether it was
+    /// present in the set.
     #[inline]
     pub fn to_back<Q: ?Sized>(&mut self, value: &Q) -> bool
     where
@@ -346,6 +374,29 @@ where
 {
 }
 
+/// Sets are compared lexicographically by their values in iteration order.
+impl<T, S> PartialOrd for LinkedHashSet<T, S>
+where
+    T: Eq + Hash + PartialOrd,
+    S: BuildHasher,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T, S> Ord for LinkedHashSet<T, S>
+where
+    T: Eq + Hash + Ord,
+    S: BuildHasher,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
 impl<T, S> fmt::Debug for LinkedHashSet<T, S>
 where
     T: fmt::Debug,
@@ -629,6 +680,26 @@ where
     }
 }
 
+impl<'a, T, S> DoubleEndedIterator for Intersection<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        loop {
+            match self.iter.next_back() {
+                None => return None,
+                Some(elt) => {
+                    if self.other.contains(elt) {
+                        return Some(elt);
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl<'a, T, S> fmt::Debug for Intersection<'a, T, S>
 where
     T: fmt::Debug + Eq + Hash,
@@ -678,6 +749,26 @@ where
     }
 }
 
+impl<'a, T, S> DoubleEndedIterator for Difference<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        loop {
+            match self.iter.next_back() {
+                None => return None,
+                Some(elt) => {
+                    if !self.other.contains(elt) {
+                        return Some(elt);
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl<'a, T, S> fmt::Debug for Difference<'a, T, S>
 where
     T: fmt::Debug + Eq + Hash,
@@ -716,6 +807,17 @@ where
     }
 }
 
+impl<'a, T, S> DoubleEndedIterator for SymmetricDifference<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.iter.next_back()
+    }
+}
+
 impl<'a, T, S> fmt::Debug for SymmetricDifference<'a, T, S>
 where
     T: fmt::Debug + Eq + Hash,
@@ -764,3 +866,14 @@ where
         self.iter.size_hint()
     }
 }
+
+impl<'a, T, S> DoubleEndedIterator for Union<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.iter.next_back()
+    }
+}
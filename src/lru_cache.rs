@@ -2,6 +2,7 @@ use core::{
     borrow::Borrow,
     fmt,
     hash::{BuildHasher, Hash},
+    iter::Rev,
     usize,
 };
 
@@ -17,6 +18,7 @@ pub use crate::linked_hash_map::{
 pub struct LruCache<K, V, S = hash_map::DefaultHashBuilder> {
     map: LinkedHashMap<K, V, S>,
     max_size: usize,
+    promote_on_get: bool,
 }
 
 impl<K: Eq + Hash, V> LruCache<K, V> {
@@ -25,6 +27,7 @@ impl<K: Eq + Hash, V> LruCache<K, V> {
         LruCache {
             map: LinkedHashMap::new(),
             max_size: capacity,
+            promote_on_get: true,
         }
     }
 
@@ -43,6 +46,7 @@ impl<K, V, S> LruCache<K, V, S> {
         LruCache {
             map: LinkedHashMap::with_hasher(hash_builder),
             max_size: capacity,
+            promote_on_get: true,
         }
     }
 
@@ -51,6 +55,13 @@ impl<K, V, S> LruCache<K, V, S> {
         self.max_size
     }
 
+    /// Returns whether retrieving an entry marks it as recently used, see
+    /// `LruCacheBuilder::promote_on_get`.
+    #[inline]
+    pub fn promotes_on_get(&self) -> bool {
+        self.promote_on_get
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.map.len()
@@ -61,16 +72,47 @@ impl<K, V, S> LruCache<K, V, S> {
         self.map.is_empty()
     }
 
+    /// Returns true if inserting a new key would evict the least recently used entry.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity()
+    }
+
+    /// Returns the fraction of the capacity that is in use, `len / capacity`.
+    ///
+    /// A cache with a capacity of zero is always full, so this returns 1.0 for it.
+    #[inline]
+    pub fn utilization(&self) -> f32 {
+        if self.capacity() == 0 {
+            1.0
+        } else {
+            self.len() as f32 / self.capacity() as f32
+        }
+    }
+
+    /// Removes all entries from the cache, keeping the allocated capacity so that refilling the
+    /// cache does not allocate.
+    ///
+    /// See `LinkedHashMap::clear_retaining_capacity`.
     #[inline]
     pub fn clear(&mut self) {
-        self.map.clear();
+        self.map.clear_retaining_capacity();
     }
 
+    /// Iterates over the entries from least recently used to most recently used, *without* marking
+    /// any of them as recently used.
     #[inline]
     pub fn iter(&self) -> Iter<K, V> {
         self.map.iter()
     }
 
+    /// Iterates over the entries from most recently used to least recently used, *without* marking
+    /// any of them as recently used.
+    #[inline]
+    pub fn iter_mru(&self) -> Rev<Iter<'_, K, V>> {
+        self.map.iter().rev()
+    }
+
     #[inline]
     pub fn iter_mut(&mut self) -> IterMut<K, V> {
         self.map.iter_mut()
@@ -80,6 +122,19 @@ impl<K, V, S> LruCache<K, V, S> {
     pub fn drain(&mut self) -> Drain<K, V> {
         self.map.drain()
     }
+
+    /// Removes every entry for which the predicate returns false, *without* marking any entry as
+    /// recently used.
+    ///
+    /// The remaining entries keep their relative order in the LRU list, see
+    /// `LinkedHashMap::retain`.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.retain(f);
+    }
 }
 
 impl<K: Eq + Hash, V, S> LruCache<K, V, S>
@@ -95,6 +150,17 @@ where
         self.get_mut(key).is_some()
     }
 
+    /// Returns true if the cache contains the given key, *without* marking it as recently used and
+    /// moving it to the back of the LRU list.
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
     /// Insert a new value into the `LruCache`.
     ///
     /// If necessary, will remove the value at the front of the LRU list to make room.
@@ -142,6 +208,8 @@ where
 
     /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
     /// list.
+    ///
+    /// If the cache was built with promotion on get turned off, the entry is not moved.
     #[inline]
     pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
@@ -150,25 +218,68 @@ where
     {
         match self.map.raw_entry_mut().from_key(k) {
             linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
-                occupied.to_back();
+                if self.promote_on_get {
+                    occupied.to_back();
+                }
                 Some(occupied.into_mut())
             }
             linked_hash_map::RawEntryMut::Vacant(_) => None,
         }
     }
 
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list, or insert `default` at the back of the LRU list if the key is not present.
+    ///
+    /// This is the eager version of `LruCache::get_or_insert_with`.
+    #[inline]
+    pub fn get_or_insert(&mut self, key: K, default: V) -> &mut V {
+        self.get_or_insert_with(key, move || default)
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list, or insert the value returned by `f` at the back of the LRU list if the key is not
+    /// present.
+    ///
+    /// If necessary, will remove the value at the front of the LRU list to make room *after*
+    /// inserting, but never the value that was just inserted.  If `f` panics, the cache is left
+    /// unchanged.
+    #[inline]
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self.map.raw_entry_mut().from_key(&key) {
+            linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
+                if self.promote_on_get {
+                    occupied.to_back();
+                }
+                occupied.into_mut()
+            }
+            linked_hash_map::RawEntryMut::Vacant(vacant) => vacant
+                .insert_entry(key, f())
+                .shrink_front_into_mut(self.max_size),
+        }
+    }
+
     /// If the returned entry is vacant, it will always have room to insert a single value.  By
     /// using the entry API, you can exceed the configured capacity by 1.
     ///
     /// The returned entry is not automatically moved to the back of the LRU list.  By calling
     /// `Entry::to_back` / `Entry::to_front` you can manually control the position of this entry in
     /// the LRU list.
+    ///
+    /// If the cache was built with promotion on get turned off, `Entry::or_insert` and similar
+    /// methods do not move an existing entry either, see `LinkedHashMap::entry_no_promote`.
     #[inline]
     pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
         if self.len() > self.capacity() {
             self.remove_lru();
         }
-        self.map.entry(key)
+        if self.promote_on_get {
+            self.map.entry(key)
+        } else {
+            self.map.entry_no_promote(key)
+        }
     }
 
     /// The constructed raw entry is never automatically moved to the back of the LRU list.  By
@@ -213,8 +324,9 @@ where
 
     /// Set the new cache capacity for the `LruCache`.
     ///
-    /// If there are more entries in the `LruCache` than the new capacity will allow, they are
-    /// removed.
+    /// If there are more entries in the `LruCache` than the new capacity will allow, the least
+    /// recently used entries are removed until it fits.  Growing the capacity only updates the
+    /// limit.
     #[inline]
     pub fn set_capacity(&mut self, capacity: usize) {
         for _ in capacity..self.len() {
@@ -223,6 +335,26 @@ where
         self.max_size = capacity;
     }
 
+    /// Returns the least recently used entry, *without* marking it as recently used and moving it
+    /// to the back of the LRU list.
+    ///
+    /// If the `LruCache` is empty this will return None.
+    #[inline]
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.map.front()
+    }
+
+    /// Moves the given key to the front of the LRU list, making it the next entry to be evicted,
+    /// and returns whether it was present.
+    #[inline]
+    pub fn demote<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.to_front(k).is_some()
+    }
+
     /// Remove the least recently used entry and return it.
     ///
     /// If the `LruCache` is empty this will return None.
@@ -230,6 +362,90 @@ where
     pub fn remove_lru(&mut self) -> Option<(K, V)> {
         self.map.pop_front()
     }
+
+    /// Remove the least recently used entry and return it.
+    ///
+    /// This is an alias for `LruCache::remove_lru`.
+    #[inline]
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        self.remove_lru()
+    }
+}
+
+/// A builder for an `LruCache` with a custom hasher, pre-allocated nodes or a different eviction
+/// policy.
+#[derive(Clone, Debug)]
+pub struct LruCacheBuilder<S = hash_map::DefaultHashBuilder> {
+    capacity: usize,
+    hash_builder: S,
+    node_count: usize,
+    promote_on_get: bool,
+}
+
+impl LruCacheBuilder {
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        LruCacheBuilder {
+            capacity,
+            hash_builder: hash_map::DefaultHashBuilder::default(),
+            node_count: 0,
+            promote_on_get: true,
+        }
+    }
+}
+
+impl<S> LruCacheBuilder<S> {
+    /// Sets the maximum number of entries of the cache.
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the hasher used by the cache.
+    #[inline]
+    pub fn with_hasher<S2>(self, hash_builder: S2) -> LruCacheBuilder<S2> {
+        LruCacheBuilder {
+            capacity: self.capacity,
+            hash_builder,
+            node_count: self.node_count,
+            promote_on_get: self.promote_on_get,
+        }
+    }
+
+    /// Allocates room for `node_count` entries up front, so that the first `node_count` insertions
+    /// do not allocate, see `LinkedHashMap::with_capacity_and_hasher_and_nodes`.
+    #[inline]
+    pub fn reserve_nodes(mut self, node_count: usize) -> Self {
+        self.node_count = node_count;
+        self
+    }
+
+    /// Sets whether retrieving an entry with `LruCache::get`, `LruCache::get_mut`,
+    /// `LruCache::contains_key`, `LruCache::get_or_insert_with` or the methods of
+    /// `LruCache::entry` marks it as recently used.
+    ///
+    /// This is true by default.  When false, entries are only ever moved to the back of the list
+    /// by `LruCache::insert`, which turns the cache into a FIFO cache that evicts the least
+    /// recently *inserted* entry.  The setting is kept when the cache is serialized.
+    #[inline]
+    pub fn promote_on_get(mut self, promote_on_get: bool) -> Self {
+        self.promote_on_get = promote_on_get;
+        self
+    }
+
+    #[inline]
+    pub fn build<K, V>(self) -> LruCache<K, V, S> {
+        LruCache {
+            map: LinkedHashMap::with_capacity_and_hasher_and_nodes(
+                self.node_count,
+                self.hash_builder,
+                self.node_count,
+            ),
+            max_size: self.capacity,
+            promote_on_get: self.promote_on_get,
+        }
+    }
 }
 
 impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> Clone for LruCache<K, V, S> {
@@ -238,6 +454,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> Clone for LruCache<
         LruCache {
             map: self.map.clone(),
             max_size: self.max_size,
+            promote_on_get: self.promote_on_get,
         }
     }
 }
@@ -5,12 +5,12 @@ use core::{
 };
 
 use serde::{
-    de::{MapAccess, SeqAccess, Visitor},
-    ser::{SerializeMap, SerializeSeq},
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeTuple},
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
-use crate::{LinkedHashMap, LinkedHashSet};
+use crate::{lru_cache::LruCacheBuilder, LinkedHashMap, LinkedHashSet, LruCache};
 
 // LinkedHashMap impls
 
@@ -159,3 +159,106 @@ where
         deserializer.deserialize_seq(LinkedHashSetVisitor::default())
     }
 }
+
+// LruCache impls
+
+// An `LruCache` is serialized as a tuple of its capacity, whether it promotes entries on get, and
+// its entries from least to most recently used.
+impl<K, V, S> Serialize for LruCache<K, V, S>
+where
+    K: Serialize + Eq + Hash,
+    V: Serialize,
+    S: BuildHasher,
+{
+    #[inline]
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        struct Entries<'a, K, V, S>(&'a LruCache<K, V, S>);
+
+        impl<'a, K: Serialize, V: Serialize, S> Serialize for Entries<'a, K, V, S> {
+            #[inline]
+            fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+                let mut map_serializer = serializer.serialize_map(Some(self.0.len()))?;
+                for (k, v) in self.0 {
+                    map_serializer.serialize_key(k)?;
+                    map_serializer.serialize_value(v)?;
+                }
+                map_serializer.end()
+            }
+        }
+
+        let mut tuple_serializer = serializer.serialize_tuple(3)?;
+        tuple_serializer.serialize_element(&self.capacity())?;
+        tuple_serializer.serialize_element(&self.promotes_on_get())?;
+        tuple_serializer.serialize_element(&Entries(self))?;
+        tuple_serializer.end()
+    }
+}
+
+// Entries are inserted in order, so if there are more entries than the capacity allows, the least
+// recently used ones are evicted.
+impl<'de, K, V, S> Deserialize<'de> for LruCache<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Debug)]
+        pub struct LruCacheVisitor<K, V, S> {
+            marker: PhantomData<LruCache<K, V, S>>,
+        }
+
+        impl<K, V, S> LruCacheVisitor<K, V, S> {
+            fn new() -> Self {
+                LruCacheVisitor {
+                    marker: PhantomData,
+                }
+            }
+        }
+
+        impl<K, V, S> Default for LruCacheVisitor<K, V, S> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<'de, K, V, S> Visitor<'de> for LruCacheVisitor<K, V, S>
+        where
+            K: Deserialize<'de> + Eq + Hash,
+            V: Deserialize<'de>,
+            S: BuildHasher + Default,
+        {
+            type Value = LruCache<K, V, S>;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a tuple of a capacity, a promotion flag and a map"
+                )
+            }
+
+            #[inline]
+            fn visit_seq<SA: SeqAccess<'de>>(self, mut seq: SA) -> Result<Self::Value, SA::Error> {
+                let capacity = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let promote_on_get = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let entries: LinkedHashMap<K, V, S> = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+
+                let mut cache = LruCacheBuilder::new(capacity)
+                    .with_hasher(S::default())
+                    .promote_on_get(promote_on_get)
+                    .build();
+                cache.extend(entries);
+
+                Ok(cache)
+            }
+        }
+
+        deserializer.deserialize_tuple(3, LruCacheVisitor::default())
+    }
+}
@@ -0,0 +1,217 @@
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+
+use hashbrown::hash_map;
+
+use crate::linked_hash_map::LinkedHashMap;
+
+/// A scan resistant cache using a simplified 2Q eviction policy.
+///
+/// Entries are first inserted into a *recent* queue, and are only promoted to a *frequent* queue
+/// when they are accessed again while still cached.  Both queues are kept in LRU order and have
+/// independent capacities.  Entries evicted from the frequent queue are demoted to the back of the
+/// recent queue, so entries only ever leave the cache from the front of the recent queue.  A
+/// one-off scan over many keys thus only churns the recent queue and never evicts frequently used
+/// entries.
+pub struct TwoQueueCache<K, V, S = hash_map::DefaultHashBuilder> {
+    recent: LinkedHashMap<K, V, S>,
+    frequent: LinkedHashMap<K, V, S>,
+    recent_size: usize,
+    frequent_size: usize,
+}
+
+impl<K: Eq + Hash, V> TwoQueueCache<K, V> {
+    /// Creates a cache with the given capacities for the recent and frequent queues.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `recent_capacity` is zero, since every new key is inserted into the recent queue
+    /// and would be evicted immediately.  A `frequent_capacity` of zero is allowed, and turns the
+    /// cache into a plain LRU cache.
+    #[inline]
+    pub fn new(recent_capacity: usize, frequent_capacity: usize) -> Self {
+        assert!(recent_capacity > 0, "recent capacity must not be zero");
+        TwoQueueCache {
+            recent: LinkedHashMap::new(),
+            frequent: LinkedHashMap::new(),
+            recent_size: recent_capacity,
+            frequent_size: frequent_capacity,
+        }
+    }
+}
+
+impl<K, V, S> TwoQueueCache<K, V, S> {
+    /// Like `TwoQueueCache::new`, but uses the given hasher for both queues.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `recent_capacity` is zero.
+    #[inline]
+    pub fn with_hasher(recent_capacity: usize, frequent_capacity: usize, hash_builder: S) -> Self
+    where
+        S: Clone,
+    {
+        assert!(recent_capacity > 0, "recent capacity must not be zero");
+        TwoQueueCache {
+            recent: LinkedHashMap::with_hasher(hash_builder.clone()),
+            frequent: LinkedHashMap::with_hasher(hash_builder),
+            recent_size: recent_capacity,
+            frequent_size: frequent_capacity,
+        }
+    }
+
+    #[inline]
+    pub fn recent_capacity(&self) -> usize {
+        self.recent_size
+    }
+
+    #[inline]
+    pub fn frequent_capacity(&self) -> usize {
+        self.frequent_size
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.recent.len() + self.frequent.len()
+    }
+
+    /// The number of entries in the recent queue, which have only been accessed once.
+    #[inline]
+    pub fn recent_len(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// The number of entries in the frequent queue, which have been accessed more than once.
+    #[inline]
+    pub fn frequent_len(&self) -> usize {
+        self.frequent.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty() && self.frequent.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.recent.clear();
+        self.frequent.clear();
+    }
+}
+
+impl<K: Eq + Hash, V, S> TwoQueueCache<K, V, S>
+where
+    S: BuildHasher,
+{
+    /// Returns true if the cache contains the given key, *without* counting it as an access.
+    #[inline]
+    pub fn contains<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.frequent.contains_key(k) || self.recent.contains_key(k)
+    }
+
+    /// Get the value for the given key, *without* counting it as an access.
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.frequent.get(k).or_else(|| self.recent.get(k))
+    }
+
+    /// Retrieve the given key, counting it as an access.
+    ///
+    /// An entry in the recent queue is promoted to the back of the frequent queue, and an entry in
+    /// the frequent queue is moved to its back.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(k).map(|v| &*v)
+    }
+
+    /// Retrieve the given key mutably, counting it as an access, see `TwoQueueCache::get`.
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.frequent.contains_key(k) {
+            return self.frequent.to_back(k);
+        }
+        if self.frequent_size == 0 {
+            return self.recent.to_back(k);
+        }
+        let (k, v) = self.recent.remove_entry(k)?;
+        self.promote(k, v);
+        self.frequent.iter_mut().next_back().map(|(_, v)| v)
+    }
+
+    /// Insert a new value into the cache, counting it as an access.
+    ///
+    /// A new key is inserted at the back of the recent queue, removing the entry at the front of
+    /// the recent queue if necessary to make room.  A key that is already present is promoted as
+    /// by `TwoQueueCache::get`, and its previous value is returned.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if self.frequent.contains_key(&k) {
+            return self.frequent.insert(k, v);
+        }
+        if self.frequent_size > 0 {
+            if let Some(old) = self.recent.remove(&k) {
+                self.promote(k, v);
+                return Some(old);
+            }
+        }
+        let old = self.recent.insert(k, v);
+        self.shrink_recent();
+        old
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.frequent.remove(k).or_else(|| self.recent.remove(k))
+    }
+
+    // Inserts an entry at the back of the frequent queue, demoting entries from its front to the
+    // recent queue to make room.  The frequent capacity must not be zero.
+    fn promote(&mut self, k: K, v: V) {
+        self.frequent.insert(k, v);
+        while self.frequent.len() > self.frequent_size {
+            let (k, v) = self.frequent.pop_front().unwrap();
+            self.recent.insert(k, v);
+        }
+        self.shrink_recent();
+    }
+
+    fn shrink_recent(&mut self) {
+        while self.recent.len() > self.recent_size {
+            self.recent.pop_front();
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for TwoQueueCache<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TwoQueueCache")
+            .field("recent", &self.recent)
+            .field("frequent", &self.frequent)
+            .finish()
+    }
+}
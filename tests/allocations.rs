@@ -0,0 +1,408 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::{Cell, RefCell},
+    mem,
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use allocator_api2::alloc::{AllocError, Allocator, Global};
+use hashlink::LinkedHashMap;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Returns the number of allocations made on the current thread while running `f`.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(|a| a.get());
+    let r = f();
+    let after = ALLOCATIONS.with(|a| a.get());
+    (r, after - before)
+}
+
+#[test]
+fn test_clear_retaining_capacity_refill() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+
+    for _ in 0..3 {
+        let ((), allocations) = count_allocations(|| {
+            map.clear_retaining_capacity();
+            for i in 0..100 {
+                map.insert(i, i);
+            }
+        });
+        assert_eq!(allocations, 0);
+    }
+}
+
+#[test]
+fn test_shrink_free_list() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    for i in 0..100 {
+        map.remove(&i);
+    }
+
+    map.shrink_free_list(10);
+
+    let ((), allocations) = count_allocations(|| {
+        for i in 0..10 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+
+    let ((), allocations) = count_allocations(|| {
+        map.insert(10, 10);
+    });
+    assert_eq!(allocations, 1);
+
+    map.shrink_free_list(0);
+    map.shrink_free_list(100);
+    assert_eq!(map.len(), 11);
+}
+
+#[test]
+fn test_extend_reserves() {
+    let entries = (0..1000).map(|i| (i, i)).collect::<Vec<_>>();
+
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    let ((), allocations) = count_allocations(|| map.extend(entries.iter().copied()));
+    // One table allocation, one guard node and one node per entry.
+    assert_eq!(allocations, 1002);
+
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    let ((), allocations) = count_allocations(|| map.extend(entries.iter().map(|(k, v)| (k, v))));
+    assert_eq!(allocations, 1002);
+    assert_eq!(map.len(), 1000);
+}
+
+#[test]
+fn test_clone_allocates_table_once() {
+    let mut map = (0..1000).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    for i in 0..500 {
+        map.remove(&i);
+    }
+
+    let (clone, allocations) = count_allocations(|| map.clone());
+    // One table allocation, one guard node and one node per entry.
+    assert_eq!(allocations, 502);
+    assert_eq!(clone, map);
+    assert!(clone.capacity() >= 500);
+}
+
+#[test]
+fn test_clone_from_reuses_nodes() {
+    let mut source = LinkedHashMap::new();
+    for i in 0..100 {
+        source.insert(i, i);
+    }
+
+    let mut dst = LinkedHashMap::new();
+    dst.clone_from(&source);
+    assert_eq!(dst, source);
+
+    for round in 0..3 {
+        source.insert(round, round + 100);
+        let ((), allocations) = count_allocations(|| dst.clone_from(&source));
+        assert_eq!(allocations, 0);
+        assert_eq!(dst, source);
+    }
+}
+
+#[test]
+fn test_reserve_nodes() {
+    let mut map = LinkedHashMap::new();
+    let ((), allocations) = count_allocations(|| {
+        map.reserve(100);
+        map.reserve_nodes(100);
+    });
+    // One table allocation, one guard node and 100 free nodes.
+    assert_eq!(allocations, 102);
+
+    let ((), allocations) = count_allocations(|| {
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+
+    let (_, allocations) = count_allocations(|| map.insert(100, 100));
+    assert_eq!(allocations, 1);
+}
+
+#[test]
+fn test_reserve_full() {
+    let mut map = LinkedHashMap::new();
+    let ((), allocations) = count_allocations(|| map.reserve_full(100));
+    // One table allocation, one guard node and 100 free nodes.
+    assert_eq!(allocations, 102);
+
+    let ((), allocations) = count_allocations(|| {
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+
+    // Nodes already on the free list are counted towards the reservation.
+    for i in 0..40 {
+        map.remove(&i);
+    }
+    map.reserve(50);
+    let ((), allocations) = count_allocations(|| map.reserve_full(50));
+    assert_eq!(allocations, 10);
+
+    let ((), allocations) = count_allocations(|| {
+        for i in 100..150 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_with_capacity_and_hasher_and_nodes() {
+    let mut map = LinkedHashMap::with_capacity_and_hasher_and_nodes(
+        50,
+        hashbrown::hash_map::DefaultHashBuilder::default(),
+        50,
+    );
+    assert!(map.capacity() >= 50);
+
+    let ((), allocations) = count_allocations(|| {
+        for i in 0..50 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+    assert_eq!(map.len(), 50);
+}
+
+// Tracks the number of live allocations made through it.
+#[derive(Clone, Default)]
+struct TrackingAllocator(Rc<Cell<isize>>);
+
+unsafe impl Allocator for TrackingAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_custom_allocator() {
+    let alloc = TrackingAllocator::default();
+    let live = alloc.0.clone();
+
+    let mut map = LinkedHashMap::new_in(alloc);
+    for i in 0..10 {
+        map.insert(i, i);
+    }
+    // One table allocation, one guard node and ten value nodes.
+    assert_eq!(live.get(), 12);
+    map.remove(&3);
+    map.shrink_to_fit();
+    assert_eq!(live.get(), 11);
+
+    let mut map2 = map.clone();
+    assert_eq!(live.get(), 22);
+    map2.clear();
+    assert_eq!(live.get(), 13);
+    drop(map2);
+    assert_eq!(live.get(), 11);
+
+    let mut iter = map.into_iter();
+    assert_eq!(iter.next(), Some((0, 0)));
+    assert_eq!(iter.next_back(), Some((9, 9)));
+    drop(iter);
+    assert_eq!(live.get(), 0);
+}
+
+// Frees any allocations that are still live when it is dropped, so memory leaked by a map using it
+// is reclaimed.
+#[derive(Default)]
+struct CollectingAllocator(RefCell<Vec<(NonNull<u8>, Layout)>>);
+
+unsafe impl Allocator for CollectingAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = Global.allocate(layout)?;
+        self.0.borrow_mut().push((ptr.cast(), layout));
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.borrow_mut().retain(|&(p, _)| p != ptr);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+impl Drop for CollectingAllocator {
+    fn drop(&mut self) {
+        for (ptr, layout) in self.0.get_mut().drain(..) {
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+}
+
+#[test]
+fn test_forget_drain() {
+    let alloc = CollectingAllocator::default();
+    let mut map = LinkedHashMap::new_in(&alloc);
+    for i in 0..10 {
+        map.insert(i, i * 10);
+    }
+
+    let mut drain = map.drain();
+    assert_eq!(drain.next(), Some((0, 0)));
+    assert_eq!(drain.next_back(), Some((9, 90)));
+    mem::forget(drain);
+
+    // Forgetting the `Drain` leaks the 8 entries it did not yield, but leaves the map empty and
+    // usable.
+    assert!(map.is_empty());
+    assert_eq!(map.iter().next(), None);
+    assert_eq!(map.get(&5), None);
+
+    for i in 20..25 {
+        map.insert(i, i);
+    }
+    assert_eq!(map.remove(&22), Some(22));
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec![20, 21, 23, 24]
+    );
+    assert_eq!(map.drain().collect::<Vec<_>>().len(), 4);
+    assert!(map.is_empty());
+
+    drop(map);
+    // Only the leaked nodes remain allocated.
+    assert_eq!(alloc.0.borrow().len(), 8);
+}
+
+#[test]
+fn test_drain_retains_capacity() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    let capacity = map.capacity();
+
+    for _ in 0..3 {
+        assert_eq!(map.drain().count(), 100);
+        assert_eq!(map.capacity(), capacity);
+
+        let ((), allocations) = count_allocations(|| {
+            for i in 0..100 {
+                map.insert(i, i);
+            }
+        });
+        assert_eq!(allocations, 0);
+    }
+}
+
+#[test]
+fn test_shrink_to_fit_retaining() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    for i in 10..100 {
+        map.remove(&i);
+    }
+
+    map.shrink_to_fit_retaining(20);
+    assert!(map.capacity() < 100);
+    assert_eq!(map.len(), 10);
+
+    // Grow the table up front, so that only node allocations are counted.
+    map.reserve(20);
+    let ((), allocations) = count_allocations(|| {
+        for i in 10..30 {
+            map.insert(i, i);
+        }
+    });
+    // Every node comes from the free list.
+    assert_eq!(allocations, 0);
+
+    let (_, allocations) = count_allocations(|| map.insert(30, 30));
+    assert_eq!(allocations, 1);
+}
+
+#[test]
+fn test_extend_map_moves_nodes() {
+    let mut a = (0..1000).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    let b = (1000..2000)
+        .map(|i| (i, i))
+        .collect::<LinkedHashMap<_, _>>();
+    a.reserve(1000);
+
+    let ((), allocations) = count_allocations(|| a.extend_map(b));
+    assert_eq!(allocations, 0);
+    assert_eq!(a.len(), 2000);
+    assert!(a.keys().copied().eq(0..2000));
+}
+
+#[test]
+fn test_clone_empty() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 1);
+
+    let (_empty, allocations) = count_allocations(|| map.clone_empty());
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_retain_and_compact() {
+    let alloc = TrackingAllocator::default();
+    let live = alloc.0.clone();
+
+    let mut map = LinkedHashMap::new_in(alloc);
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    // One table allocation, one guard node and 100 value nodes.
+    assert_eq!(live.get(), 102);
+
+    map.retain_and_compact(|&k, _| k < 10, 5);
+    assert_eq!(map.len(), 10);
+    assert_eq!(live.get(), 17);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+
+    for i in 10..15 {
+        map.insert(i, i);
+    }
+    assert_eq!(live.get(), 17);
+    map.insert(15, 15);
+    assert_eq!(live.get(), 18);
+}
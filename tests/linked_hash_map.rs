@@ -1,5 +1,16 @@
+use std::hash::{BuildHasher, Hash};
+
 use hashlink::{linked_hash_map, LinkedHashMap};
 
+// Checks the internal consistency of the map, which is only possible with debug assertions.
+#[cfg(debug_assertions)]
+fn check_invariants<K: Hash + Eq, V, S: BuildHasher>(map: &LinkedHashMap<K, V, S>) {
+    map.check_invariants();
+}
+
+#[cfg(not(debug_assertions))]
+fn check_invariants<K: Hash + Eq, V, S: BuildHasher>(_: &LinkedHashMap<K, V, S>) {}
+
 #[allow(dead_code)]
 fn assert_covariance() {
     fn set<'new>(v: LinkedHashMap<&'static str, ()>) -> LinkedHashMap<&'new str, ()> {
@@ -64,6 +75,7 @@ fn test_insert_update() {
     map.insert("1".to_string(), vec![10, 19]);
     assert_eq!(map.get(&"1".to_string()), Some(&vec![10, 19]));
     assert_eq!(map.len(), 1);
+    check_invariants(&map);
 }
 
 #[test]
@@ -95,6 +107,7 @@ fn test_entry_insert_vacant() {
         }
         _ => panic!("fail"),
     }
+    check_invariants(&map);
 }
 
 #[test]
@@ -115,6 +128,7 @@ fn test_remove() {
     assert_eq!(map.get(&6), Some(&60));
     assert_eq!(map.get(&7), Some(&70));
     assert_eq!(map.get(&8), Some(&80));
+    check_invariants(&map);
 }
 
 #[test]
@@ -141,6 +155,7 @@ fn test_pop() {
     assert!(map.get(&4).is_none());
     assert_eq!(map.pop_back(), Some((3, 30)));
     assert!(map.get(&3).is_none());
+    check_invariants(&map);
 }
 
 #[test]
@@ -179,6 +194,59 @@ fn test_move() {
 
     to_front(&mut map, 2);
     assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 4, 5, 1, 3]);
+    check_invariants(&map);
+}
+
+#[test]
+fn test_swap_keys() {
+    let mut map: LinkedHashMap<i32, i32> = (1..=5).map(|i| (i, i * 10)).collect();
+
+    assert!(map.swap_keys(&1, &5));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![5, 2, 3, 4, 1]);
+
+    assert!(map.swap_keys(&2, &3));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![5, 3, 2, 4, 1]);
+
+    assert!(map.swap_keys(&4, &2));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![5, 3, 4, 2, 1]);
+
+    assert!(!map.swap_keys(&3, &3));
+    assert!(!map.swap_keys(&3, &6));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![5, 3, 4, 2, 1]);
+    assert_eq!(
+        map.keys().rev().copied().collect::<Vec<_>>(),
+        vec![1, 2, 4, 3, 5]
+    );
+    assert_eq!(map[&5], 50);
+
+    let mut map: LinkedHashMap<i32, i32> = (1..=2).map(|i| (i, i)).collect();
+    assert!(map.swap_keys(&2, &1));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 1]);
+    assert!(map.swap_keys(&2, &1));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn test_swap_with() {
+    let mut front = (0..10).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    let mut back = (20..25)
+        .rev()
+        .map(|i| (i, i))
+        .collect::<LinkedHashMap<_, _>>();
+
+    front.swap_with(&mut back);
+    assert_eq!(
+        front.keys().copied().collect::<Vec<_>>(),
+        vec![24, 23, 22, 21, 20]
+    );
+    assert_eq!(
+        back.keys().copied().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(front.get(&22), Some(&22));
+    assert_eq!(back.get(&3), Some(&3));
+    check_invariants(&front);
+    check_invariants(&back);
 }
 
 #[test]
@@ -190,6 +258,43 @@ fn test_clear() {
     assert!(map.get(&1).is_none());
     assert!(map.get(&2).is_none());
     assert!(map.is_empty());
+    check_invariants(&map);
+}
+
+#[test]
+fn test_clear_retaining_capacity() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    let capacity = map.capacity();
+
+    for _ in 0..3 {
+        map.clear_retaining_capacity();
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), capacity);
+        assert!(map.get(&0).is_none());
+
+        for i in 0..100 {
+            map.insert(i, i * 2);
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.front(), Some((&0, &0)));
+        assert_eq!(map.back(), Some((&99, &198)));
+    }
+}
+
+#[test]
+fn test_clear_values() {
+    let mut map = LinkedHashMap::new();
+    map.insert(3, 30);
+    map.insert(1, 10);
+    map.insert(2, 20);
+    map.clear_values();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 1, 2]);
+    assert!(map.values().all(|v| *v == 0));
 }
 
 #[test]
@@ -236,6 +341,32 @@ fn test_iter() {
     assert_eq!(None, mixed_iter.next_back());
 }
 
+#[test]
+fn test_iter_from() {
+    let mut map = LinkedHashMap::new();
+    map.insert("a", 10);
+    map.insert("b", 20);
+    map.insert("c", 30);
+    map.insert("d", 40);
+
+    assert!(map.iter_from("e").is_none());
+
+    let iter = map.iter_from("b").unwrap();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(
+        iter.collect::<Vec<_>>(),
+        vec![(&"b", &20), (&"c", &30), (&"d", &40)]
+    );
+
+    let mut iter = map.iter_from("c").unwrap();
+    assert_eq!(iter.next_back(), Some((&"d", &40)));
+    assert_eq!(iter.next_back(), Some((&"c", &30)));
+    assert_eq!(iter.next_back(), None);
+
+    assert_eq!(map.iter_from("d").unwrap().len(), 1);
+    assert_eq!(map.iter_from("a").unwrap().len(), 4);
+}
+
 #[test]
 fn test_borrow() {
     #[derive(PartialEq, Eq, Hash)]
@@ -345,6 +476,8 @@ fn test_consuming_iter_with_free_list() {
     map.remove("a");
     map.remove("b");
 
+    check_invariants(&map);
+
     let mut iter = map.into_iter();
     assert_eq!(Some(("c", 30)), iter.next());
     assert_eq!(None, iter.next());
@@ -439,6 +572,7 @@ fn test_drain() {
     map.insert("c", Counter(c.clone()));
 
     map.drain();
+    check_invariants(&map);
     assert_eq!(map.len(), 0);
 
     assert_eq!(a.get(), 3);
@@ -491,12 +625,45 @@ fn test_retain() {
     map.insert(4, Counter(Rc::clone(&c)));
 
     map.retain(|k, _| *k % 2 == 0);
+    check_invariants(&map);
 
     assert!(c.get() == 2);
     drop(map);
     assert!(c.get() == 4);
 }
 
+#[test]
+fn test_retain_with_index() {
+    let mut map: LinkedHashMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+    map.to_front(&5);
+
+    let mut seen = Vec::new();
+    map.retain_with_index(|i, k, _| {
+        seen.push((i, *k));
+        i < 3 || *k % 2 == 0
+    });
+
+    assert_eq!(
+        seen,
+        vec![
+            (0, 5),
+            (1, 0),
+            (2, 1),
+            (3, 2),
+            (4, 3),
+            (5, 4),
+            (6, 6),
+            (7, 7),
+            (8, 8),
+            (9, 9)
+        ]
+    );
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec![5, 0, 1, 2, 4, 6, 8]
+    );
+}
+
 #[test]
 fn test_order_equality() {
     let xs = [1, 2, 3, 4, 5, 6];
@@ -539,6 +706,7 @@ fn test_replace() {
         .iter()
         .map(|(k, v)| (*k, *v))
         .eq([(1, 1), (2, 6), (4, 4), (3, 5)].iter().copied()));
+    check_invariants(&map);
 }
 
 #[test]
@@ -553,6 +721,7 @@ fn test_reserve() {
     assert!(map.capacity() - map.len() < 100);
     map.reserve(100);
     assert!(map.capacity() - map.len() >= 100);
+    check_invariants(&map);
 }
 
 #[test]
@@ -574,4 +743,1731 @@ fn test_shrink_to_fit_resize() {
     for i in 50..100 {
         assert_eq!(map.get(&i).unwrap(), &i);
     }
+    check_invariants(&map);
+}
+
+#[test]
+fn test_replace_entry_with() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+    map.insert(3, 30);
+
+    match map.entry(2).and_replace_entry_with(|k, v| {
+        assert_eq!(*k, 2);
+        assert_eq!(v, 20);
+        Some(v + 1)
+    }) {
+        linked_hash_map::Entry::Occupied(e) => assert_eq!(*e.get(), 21),
+        linked_hash_map::Entry::Vacant(_) => panic!(),
+    }
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(1, 10), (2, 21), (3, 30)]
+    );
+
+    match map.entry(1).and_replace_entry_with(|_, _| None) {
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+        linked_hash_map::Entry::Vacant(e) => assert_eq!(*e.key(), 1),
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 3]);
+
+    match map
+        .raw_entry_mut()
+        .from_key(&3)
+        .and_replace_entry_with(|_, _| None)
+    {
+        linked_hash_map::RawEntryMut::Occupied(_) => panic!(),
+        linked_hash_map::RawEntryMut::Vacant(e) => {
+            e.insert(4, 40);
+        }
+    }
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(2, 21), (4, 40)]
+    );
+}
+
+#[test]
+fn test_replace_entry_with_panic() {
+    use std::{
+        cell::Cell,
+        panic::{catch_unwind, AssertUnwindSafe},
+        rc::Rc,
+    };
+
+    struct Counter(Rc<Cell<u32>>);
+
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let c = Rc::new(Cell::new(0));
+
+    let mut map = LinkedHashMap::new();
+    map.insert(1, Counter(Rc::clone(&c)));
+    map.insert(2, Counter(Rc::clone(&c)));
+    map.insert(3, Counter(Rc::clone(&c)));
+
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        map.entry(2)
+            .and_replace_entry_with(|_, _| -> Option<Counter> { panic!() });
+    }));
+    assert!(res.is_err());
+    assert_eq!(c.get(), 1);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 3]);
+
+    map.insert(4, Counter(Rc::clone(&c)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 3, 4]);
+    drop(map);
+    assert_eq!(c.get(), 4);
+}
+
+#[test]
+fn test_entry_insert() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+    map.insert(3, 30);
+
+    let mut entry = map.entry(4).insert(40);
+    assert_eq!(*entry.key(), 4);
+    assert_eq!(*entry.get(), 40);
+    entry.to_front();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 1, 2, 3]);
+
+    let entry = map.entry(1).insert(11);
+    assert_eq!(*entry.get(), 11);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 2, 3, 1]);
+
+    assert_eq!(map.entry(2).insert(22).remove(), 22);
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(4, 40), (3, 30), (1, 11)]
+    );
+}
+
+#[test]
+fn test_entry_or_default() {
+    let mut map: LinkedHashMap<&str, Vec<i32>> = LinkedHashMap::new();
+    map.entry("a").or_default().push(1);
+    map.entry("b").or_default().push(2);
+    map.entry("a").or_default().push(3);
+
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        vec![(&"b", &vec![2]), (&"a", &vec![1, 3])]
+    );
+}
+
+#[test]
+fn test_entry_or_insert_with_key() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+
+    assert_eq!(*map.entry(3).or_insert_with_key(|k| k * 100), 300);
+    assert_eq!(*map.entry(1).or_insert_with_key(|_| panic!()), 10);
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(2, 20), (3, 300), (1, 10)]
+    );
+}
+
+#[test]
+fn test_vacant_insert_entry() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+
+    match map.entry(3) {
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+        linked_hash_map::Entry::Vacant(e) => {
+            let mut e = e.insert_entry(30);
+            assert_eq!(*e.key(), 3);
+            assert_eq!(*e.get(), 30);
+            e.to_front();
+        }
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 1, 2]);
+}
+
+#[test]
+fn test_get_equiv() {
+    use std::hash::{Hash, Hasher};
+
+    struct Pair<'a>(&'a str, &'a str);
+
+    impl Hash for Pair<'_> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            (self.0, self.1).hash(state);
+        }
+    }
+
+    // Implementations of hashbrown's trait are accepted directly.
+    impl hashbrown::Equivalent<(String, String)> for Pair<'_> {
+        fn equivalent(&self, key: &(String, String)) -> bool {
+            self.0 == key.0 && self.1 == key.1
+        }
+    }
+
+    let mut map = LinkedHashMap::new();
+    map.insert(("a".to_owned(), "b".to_owned()), 1);
+    map.insert(("c".to_owned(), "d".to_owned()), 2);
+
+    assert_eq!(map.get_equiv(&Pair("a", "b")), Some(&1));
+    assert_eq!(map.get_equiv(&Pair("c", "d")), Some(&2));
+    assert_eq!(map.get_equiv(&Pair("a", "d")), None);
+
+    *map.get_mut_equiv(&Pair("c", "d")).unwrap() = 3;
+    assert_eq!(map.get_equiv(&Pair("c", "d")), Some(&3));
+    assert_eq!(map.get_mut_equiv(&Pair("b", "c")), None);
+
+    // Plain borrowed keys still work through the blanket implementation.
+    let mut map = LinkedHashMap::new();
+    map.insert("a".to_owned(), 1);
+    assert_eq!(map.get_equiv("a"), Some(&1));
+}
+
+#[test]
+fn test_hasher() {
+    use std::hash::{BuildHasher, Hash, Hasher};
+
+    let mut map = LinkedHashMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+
+    #[allow(clippy::manual_hash_one)]
+    let hash = |k: &&str| {
+        let mut hasher = map.hasher().build_hasher();
+        k.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    assert_eq!(
+        map.raw_entry().from_key_hashed_nocheck(hash(&"b"), &"b"),
+        Some((&"b", &2))
+    );
+
+    let mut other: LinkedHashMap<&str, i32, _> = LinkedHashMap::with_hasher(map.hasher().clone());
+    other.insert("b", 2);
+    assert_eq!(
+        other.raw_entry().from_key_hashed_nocheck(hash(&"b"), &"b"),
+        Some((&"b", &2))
+    );
+}
+
+#[test]
+fn test_clone_empty() {
+    use std::hash::Hasher;
+
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 1);
+
+    let mut empty = map.clone_empty();
+    assert!(empty.is_empty());
+    assert_eq!(empty.capacity(), 0);
+
+    // Sharing the hasher means precomputed hashes are valid for both maps.
+    empty.insert(1, 2);
+    #[allow(clippy::manual_hash_one)]
+    let hash = {
+        let mut hasher = map.hasher().build_hasher();
+        1.hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(empty.get_hashed_nocheck(hash, &1), Some(&2));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_from_hash_map() {
+    use std::collections::HashMap;
+
+    let hash_map: HashMap<i32, i32> = (0..10).map(|i| (i, i * 10)).collect();
+    let order = hash_map.keys().copied().collect::<Vec<_>>();
+
+    let map = LinkedHashMap::from(hash_map);
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), order);
+    for i in 0..10 {
+        assert_eq!(map[&i], i * 10);
+    }
+}
+
+#[test]
+fn test_map_values() {
+    let mut map = LinkedHashMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+    map.to_front(&"c");
+
+    let strings = map.map_values(|v| v.to_string());
+    assert_eq!(
+        strings.iter().collect::<Vec<_>>(),
+        vec![
+            (&"c", &"3".to_string()),
+            (&"a", &"1".to_string()),
+            (&"b", &"2".to_string())
+        ]
+    );
+    assert_eq!(strings.hasher(), map.hasher());
+
+    let doubled = map.into_map_values(|v| v * 2);
+    assert_eq!(
+        doubled.into_iter().collect::<Vec<_>>(),
+        vec![("c", 6), ("a", 2), ("b", 4)]
+    );
+}
+
+#[test]
+fn test_ord() {
+    let a: LinkedHashMap<i32, i32> = vec![(1, 1), (2, 2)].into_iter().collect();
+    let b: LinkedHashMap<i32, i32> = vec![(2, 2), (1, 1)].into_iter().collect();
+    let c: LinkedHashMap<i32, i32> = vec![(1, 1), (2, 3)].into_iter().collect();
+    let d: LinkedHashMap<i32, i32> = vec![(1, 1)].into_iter().collect();
+
+    assert!(a < b);
+    assert!(a < c);
+    assert!(d < a);
+    assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+
+    let mut sorted = vec![b.clone(), c.clone(), a.clone(), d.clone()];
+    sorted.sort();
+    assert_eq!(sorted, vec![d, a, c, b]);
+}
+
+#[test]
+fn test_rotate_to_front() {
+    let mut map: LinkedHashMap<i32, i32> = (0..5).map(|i| (i, i)).collect();
+
+    assert!(map.rotate_to_front(&3));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 4, 0, 1, 2]);
+    assert!(map.rotate_to_front(&3));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 4, 0, 1, 2]);
+    assert!(map.rotate_to_front(&2));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 3, 4, 0, 1]);
+    assert!(!map.rotate_to_front(&10));
+
+    assert_eq!(map.pop_front(), Some((2, 2)));
+    assert_eq!(map.pop_back(), Some((1, 1)));
+    map.insert(5, 5);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 4, 0, 5]);
+    assert_eq!(
+        map.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![5, 0, 4, 3]
+    );
+}
+
+#[test]
+fn test_eq_unordered() {
+    let a: LinkedHashMap<i32, i32> = vec![(1, 10), (2, 20), (3, 30)].into_iter().collect();
+    let b: LinkedHashMap<i32, i32> = vec![(3, 30), (1, 10), (2, 20)].into_iter().collect();
+    assert!(a.eq_unordered(&b));
+    assert!(a != b);
+
+    let c: LinkedHashMap<i32, i32> = vec![(3, 30), (1, 10), (2, 21)].into_iter().collect();
+    assert!(!a.eq_unordered(&c));
+    let d: LinkedHashMap<i32, i32> = vec![(3, 30), (1, 10)].into_iter().collect();
+    assert!(!a.eq_unordered(&d));
+    assert!(!d.eq_unordered(&a));
+}
+
+#[test]
+fn test_drain_peek() {
+    let mut map: LinkedHashMap<i32, i32> = (0..4).map(|i| (i, i * 10)).collect();
+
+    let mut drain = map.drain();
+    assert_eq!(drain.peek_front(), Some((&0, &0)));
+    assert_eq!(drain.peek_back(), Some((&3, &30)));
+    assert_eq!(drain.next(), Some((0, 0)));
+    assert_eq!(drain.next_back(), Some((3, 30)));
+    assert_eq!(drain.peek_front(), Some((&1, &10)));
+    assert_eq!(drain.peek_back(), Some((&2, &20)));
+    assert_eq!(drain.next(), Some((1, 10)));
+    assert_eq!(drain.peek_front(), Some((&2, &20)));
+    assert_eq!(drain.peek_back(), Some((&2, &20)));
+    assert_eq!(drain.next_back(), Some((2, 20)));
+    assert_eq!(drain.peek_front(), None);
+    assert_eq!(drain.peek_back(), None);
+    drop(drain);
+
+    let mut empty: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    assert_eq!(empty.drain().peek_front(), None);
+}
+
+#[test]
+fn test_replace_panic_on_drop() {
+    use std::{
+        hash::{Hash, Hasher},
+        panic::{catch_unwind, AssertUnwindSafe},
+    };
+
+    // Compares and hashes only by the id, panics on drop if armed.
+    struct Bomb(i32, bool);
+
+    impl PartialEq for Bomb {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Bomb {}
+
+    impl Hash for Bomb {
+        fn hash<H: Hasher>(&self, h: &mut H) {
+            self.0.hash(h);
+        }
+    }
+
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            if self.1 {
+                panic!("boom");
+            }
+        }
+    }
+
+    let mut map = LinkedHashMap::new();
+    map.insert(Bomb(1, false), Bomb(10, true));
+    map.insert(Bomb(2, true), Bomb(20, false));
+    map.insert(Bomb(3, true), Bomb(30, false));
+
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        map.insert(Bomb(1, false), Bomb(11, false));
+    }));
+    assert!(res.is_err());
+
+    let res = catch_unwind(AssertUnwindSafe(|| match map.entry(Bomb(2, false)) {
+        linked_hash_map::Entry::Occupied(occupied) => {
+            occupied.replace_entry(Bomb(21, false));
+        }
+        linked_hash_map::Entry::Vacant(_) => unreachable!(),
+    }));
+    assert!(res.is_err());
+
+    let res = catch_unwind(AssertUnwindSafe(|| match map.entry(Bomb(3, false)) {
+        linked_hash_map::Entry::Occupied(occupied) => {
+            occupied.replace_key();
+        }
+        linked_hash_map::Entry::Vacant(_) => unreachable!(),
+    }));
+    assert!(res.is_err());
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(
+        map.iter().map(|(k, v)| (k.0, v.0)).collect::<Vec<_>>(),
+        vec![(2, 21), (3, 30), (1, 11)]
+    );
+    assert_eq!(
+        map.iter()
+            .rev()
+            .map(|(k, v)| (k.0, v.0))
+            .collect::<Vec<_>>(),
+        vec![(1, 11), (3, 30), (2, 21)]
+    );
+    assert_eq!(map.get(&Bomb(2, false)).map(|v| v.0), Some(21));
+    drop(map);
+}
+
+#[test]
+fn test_get_key_value_mut() {
+    use std::hash::{Hash, Hasher};
+
+    #[derive(Debug)]
+    struct Interned {
+        name: &'static str,
+        uses: u32,
+    }
+
+    impl PartialEq for Interned {
+        fn eq(&self, other: &Self) -> bool {
+            self.name == other.name
+        }
+    }
+
+    impl Eq for Interned {}
+
+    impl Hash for Interned {
+        fn hash<H: Hasher>(&self, h: &mut H) {
+            self.name.hash(h);
+        }
+    }
+
+    let mut map = LinkedHashMap::new();
+    map.insert(Interned { name: "a", uses: 0 }, 1);
+    map.insert(Interned { name: "b", uses: 0 }, 2);
+
+    let (k, v) = map
+        .get_key_value_mut(&Interned { name: "a", uses: 0 })
+        .unwrap();
+    k.uses += 1;
+    *v += 10;
+    assert!(map
+        .get_key_value_mut(&Interned { name: "c", uses: 0 })
+        .is_none());
+
+    assert_eq!(
+        map.iter()
+            .map(|(k, v)| (k.name, k.uses, *v))
+            .collect::<Vec<_>>(),
+        vec![("a", 1, 11), ("b", 0, 2)]
+    );
+}
+
+#[test]
+fn test_raw_occupied_entry_index() {
+    let mut map: LinkedHashMap<i32, i32> = (0..5).map(|i| (i, i)).collect();
+    map.to_front(&3);
+
+    let index = |map: &mut LinkedHashMap<i32, i32>, k: i32| match map.raw_entry_mut().from_key(&k) {
+        linked_hash_map::RawEntryMut::Occupied(occupied) => Some(occupied.index()),
+        linked_hash_map::RawEntryMut::Vacant(_) => None,
+    };
+
+    assert_eq!(index(&mut map, 3), Some(0));
+    assert_eq!(index(&mut map, 0), Some(1));
+    assert_eq!(index(&mut map, 4), Some(4));
+    assert_eq!(index(&mut map, 5), None);
+}
+
+#[test]
+fn test_rev_iterators() {
+    let mut map: LinkedHashMap<i32, i32> = (0..4).map(|i| (i, i * 10)).collect();
+    let rev_keys = vec![3, 2, 1, 0];
+
+    assert_eq!(
+        map.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+        rev_keys
+    );
+    assert_eq!(
+        (&map)
+            .into_iter()
+            .rev()
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>(),
+        rev_keys
+    );
+    assert_eq!(
+        map.iter_mut().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+        rev_keys
+    );
+    assert_eq!(
+        (&mut map)
+            .into_iter()
+            .rev()
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>(),
+        rev_keys
+    );
+    assert_eq!(map.keys().rev().copied().collect::<Vec<_>>(), rev_keys);
+    assert_eq!(
+        map.values().rev().copied().collect::<Vec<_>>(),
+        vec![30, 20, 10, 0]
+    );
+    assert_eq!(
+        map.values_mut().rev().map(|v| *v).collect::<Vec<_>>(),
+        vec![30, 20, 10, 0]
+    );
+    assert_eq!(
+        map.clone()
+            .into_iter()
+            .rev()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>(),
+        rev_keys
+    );
+
+    let drain = map.drain();
+    assert_eq!(drain.len(), 4);
+    assert_eq!(drain.rev().map(|(k, _)| k).collect::<Vec<_>>(), rev_keys);
+}
+
+#[test]
+fn test_from_iter_first_wins() {
+    let events = vec![("b", 1), ("a", 2), ("b", 3), ("c", 4), ("a", 5)];
+
+    let first: LinkedHashMap<&str, i32> = LinkedHashMap::from_iter_first_wins(events.clone());
+    assert_eq!(
+        first.into_iter().collect::<Vec<_>>(),
+        vec![("b", 1), ("a", 2), ("c", 4)]
+    );
+
+    let last: LinkedHashMap<&str, i32> = events.into_iter().collect();
+    assert_eq!(
+        last.into_iter().collect::<Vec<_>>(),
+        vec![("b", 3), ("c", 4), ("a", 5)]
+    );
+}
+
+#[test]
+fn test_pop_front_while() {
+    let mut map: LinkedHashMap<i32, u32> = vec![(1, 10), (2, 20), (3, 35), (4, 15), (5, 50)]
+        .into_iter()
+        .collect();
+
+    let expired = map.pop_front_while(|_, &t| t < 30).collect::<Vec<_>>();
+    assert_eq!(expired, vec![(1, 10), (2, 20)]);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+
+    let mut iter = map.pop_front_while(|_, _| true);
+    assert_eq!(iter.next(), Some((3, 35)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 5]);
+
+    assert_eq!(map.pop_front_while(|_, _| false).next(), None);
+    assert_eq!(
+        format!("{:?}", map.pop_front_while(|_, _| false)),
+        "PopFrontWhile"
+    );
+    assert_eq!(map.pop_front_while(|_, _| true).count(), 2);
+    assert!(map.is_empty());
+    assert_eq!(map.pop_front_while(|_, _| true).next(), None);
+
+    map.insert(6, 60);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![6]);
+}
+
+#[test]
+fn test_index_usize() {
+    let mut map: LinkedHashMap<&str, i32> = LinkedHashMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+    map.insert("d", 4);
+    map.insert("e", 5);
+    map.to_front(&"d");
+
+    assert_eq!(
+        (0..map.len()).map(|i| map[i]).collect::<Vec<_>>(),
+        vec![4, 1, 2, 3, 5]
+    );
+    assert_eq!(map[&"d"], 4);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds: the len is 2 but the index is 2")]
+fn test_index_usize_out_of_bounds() {
+    let map: LinkedHashMap<i32, i32> = vec![(1, 1), (2, 2)].into_iter().collect();
+    let _ = map[2];
+}
+
+#[test]
+fn test_get_disjoint_indices_mut() {
+    let mut map: LinkedHashMap<i32, i32> = (0..5).map(|i| (i, i * 10)).collect();
+    map.to_back(&0);
+
+    let [a, b, c] = map.get_disjoint_indices_mut([4, 0, 2]).unwrap();
+    assert_eq!((*a, *b, *c), (0, 10, 30));
+    std::mem::swap(a, b);
+    *c += 1;
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(1, 0), (2, 20), (3, 31), (4, 40), (0, 10)]
+    );
+
+    assert!(map.get_disjoint_indices_mut([1, 1]).is_none());
+    assert!(map.get_disjoint_indices_mut([0, 5]).is_none());
+    assert_eq!(map.get_disjoint_indices_mut([]), Some([]));
+}
+
+#[test]
+fn test_first_last_entry() {
+    let mut map: LinkedHashMap<i32, i32> = (0..4).map(|i| (i, i * 10)).collect();
+
+    let first = map.first_entry().unwrap();
+    assert_eq!((*first.key(), *first.get()), (0, 0));
+    if *first.get() < 5 {
+        first.remove();
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    let mut last = map.last_entry().unwrap();
+    assert_eq!((*last.key(), *last.get()), (3, 30));
+    *last.get_mut() += 1;
+    last.to_front();
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(3, 31), (1, 10), (2, 20)]
+    );
+
+    map.clear();
+    assert!(map.first_entry().is_none());
+    assert!(map.last_entry().is_none());
+}
+
+// A key whose equality and hash ignore its tag, so that replacing a key is observable.
+#[derive(Debug, Clone, Copy)]
+struct Tagged(i32, &'static str);
+
+impl PartialEq for Tagged {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Tagged {}
+
+impl std::hash::Hash for Tagged {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+fn tags<V>(map: &LinkedHashMap<Tagged, V>) -> Vec<&'static str> {
+    map.keys().map(|k| k.1).collect()
+}
+
+#[test]
+fn test_first_last_entry_replace_key() {
+    let mut map = LinkedHashMap::new();
+    map.insert(Tagged(1, "a"), 10);
+    map.insert(Tagged(2, "b"), 20);
+
+    let first = map.first_entry().unwrap();
+    assert_eq!(first.replace_key_with(Tagged(1, "A")).1, "a");
+    let last = map.last_entry().unwrap();
+    assert_eq!(
+        last.replace_entry_with_key(Tagged(2, "B"), 21),
+        (Tagged(2, "b"), 20)
+    );
+    assert_eq!(tags(&map), vec!["A", "B"]);
+    assert_eq!(map.get(&Tagged(2, "")), Some(&21));
+
+    let first = map.first_entry().unwrap();
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| first.replace_key()));
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_entry_insert_replace_key() {
+    let mut map = LinkedHashMap::new();
+    map.insert(Tagged(1, "a"), 10);
+
+    let entry = map.entry(Tagged(2, "b")).insert(20);
+    assert_eq!(entry.replace_key_with(Tagged(2, "B")).1, "b");
+    let entry = map.entry(Tagged(3, "c")).insert(30);
+    assert_eq!(
+        entry.replace_entry_with_key(Tagged(3, "C"), 31),
+        (Tagged(3, "c"), 30)
+    );
+    assert_eq!(tags(&map), vec!["a", "B", "C"]);
+    assert_eq!(map.get(&Tagged(3, "")), Some(&31));
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.entry(Tagged(4, "d")).insert(40).replace_key()
+    }));
+    assert!(res.is_err());
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.entry(Tagged(5, "e")).insert(50).insert_entry(51)
+    }));
+    assert!(res.is_err());
+    assert_eq!(map.get(&Tagged(5, "")), Some(&50));
+
+    // An entry that was already occupied keeps the key it was looked up with.
+    let entry = map.entry(Tagged(1, "x")).insert(11);
+    assert_eq!(entry.replace_key().1, "a");
+    assert_eq!(tags(&map)[4], "x");
+}
+
+#[test]
+fn test_vacant_insert_entry_replace_key() {
+    let mut map = LinkedHashMap::new();
+
+    let e = match map.entry(Tagged(1, "a")) {
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+        linked_hash_map::Entry::Vacant(e) => e.insert_entry(10),
+    };
+    assert_eq!(e.replace_key_with(Tagged(1, "A")).1, "a");
+
+    let e = match map.entry(Tagged(2, "b")) {
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+        linked_hash_map::Entry::Vacant(e) => e.insert_entry(20),
+    };
+    assert_eq!(
+        e.replace_entry_with_key(Tagged(2, "B"), 21),
+        (Tagged(2, "b"), 20)
+    );
+    assert_eq!(tags(&map), vec!["A", "B"]);
+    assert_eq!(map.get(&Tagged(2, "")), Some(&21));
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        match map.entry(Tagged(3, "c")) {
+            linked_hash_map::Entry::Occupied(_) => panic!(),
+            linked_hash_map::Entry::Vacant(e) => e.insert_entry(30).insert_entry(31),
+        }
+    }));
+    assert!(res.is_err());
+    assert_eq!(map.get(&Tagged(3, "")), Some(&30));
+}
+
+#[test]
+fn test_partition() {
+    let mut map: LinkedHashMap<i32, i32> = (0..10).map(|i| (i, i * 10)).collect();
+    map.to_front(&7);
+
+    let odds = map.partition(|k, _| k % 2 == 0);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![0, 2, 4, 6, 8]);
+    assert_eq!(
+        odds.keys().copied().collect::<Vec<_>>(),
+        vec![7, 1, 3, 5, 9]
+    );
+    assert_eq!(odds.get(&3), Some(&30));
+    assert_eq!(map.get(&3), None);
+    assert_eq!(map.get(&4), Some(&40));
+
+    let mut odds = odds;
+    odds.insert(11, 110);
+    map.insert(10, 100);
+    assert_eq!(
+        odds.keys().copied().collect::<Vec<_>>(),
+        vec![7, 1, 3, 5, 9, 11]
+    );
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec![0, 2, 4, 6, 8, 10]
+    );
+
+    let all = map.partition(|_, _| false);
+    assert!(map.is_empty());
+    assert_eq!(all.len(), 6);
+    assert!(LinkedHashMap::<i32, i32>::new()
+        .partition(|_, _| false)
+        .is_empty());
+}
+
+#[test]
+fn test_append() {
+    let mut a = LinkedHashMap::new();
+    a.insert(1, 10);
+    a.insert(2, 20);
+    a.insert(3, 30);
+
+    let mut b = LinkedHashMap::new();
+    b.insert(4, 40);
+    b.insert(2, 21);
+    b.insert(5, 50);
+
+    a.append(&mut b);
+    assert!(b.is_empty());
+    assert_eq!(
+        a.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(1, 10), (3, 30), (4, 40), (2, 21), (5, 50)]
+    );
+
+    a.append(&mut b);
+    assert_eq!(a.len(), 5);
+
+    b.insert(6, 60);
+    assert_eq!(b.get(&6), Some(&60));
+}
+
+#[test]
+fn test_sorted_views() {
+    let mut map = LinkedHashMap::new();
+    map.insert(3, "c");
+    map.insert(1, "a");
+    map.insert(4, "d");
+    map.insert(2, "b");
+
+    assert_eq!(
+        map.keys_sorted().copied().collect::<Vec<_>>(),
+        vec![1, 2, 3, 4]
+    );
+    assert_eq!(
+        map.values_sorted_by_key().copied().collect::<Vec<_>>(),
+        vec!["a", "b", "c", "d"]
+    );
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 1, 4, 2]);
+
+    let empty: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    assert_eq!(empty.keys_sorted().next(), None);
+    assert_eq!(empty.values_sorted_by_key().next(), None);
+}
+
+#[test]
+fn test_get_hashed_nocheck() {
+    use std::hash::{BuildHasher, Hash, Hasher};
+
+    let mut map = LinkedHashMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+
+    #[allow(clippy::manual_hash_one)]
+    let hash = |k: &str| {
+        let mut hasher = map.hasher().build_hasher();
+        k.hash(&mut hasher);
+        hasher.finish()
+    };
+    let hash_a = hash("a");
+    let hash_c = hash("c");
+    assert_eq!(map.get_hashed_nocheck(hash_a, "a"), Some(&1));
+    assert!(map.contains_key_hashed_nocheck(hash_a, "a"));
+    assert_eq!(map.get_hashed_nocheck(hash_c, "c"), None);
+    assert!(!map.contains_key_hashed_nocheck(hash_c, "c"));
+}
+
+#[test]
+fn test_entry_key_mut() {
+    let mut map: LinkedHashMap<String, i32> = LinkedHashMap::new();
+    map.insert("a".to_owned(), 1);
+
+    let mut entry = map.entry("B".to_owned());
+    entry.key_mut().unwrap().make_ascii_lowercase();
+    assert_eq!(entry.key(), "b");
+    entry.or_insert(2);
+    assert_eq!(map.get("b"), Some(&2));
+    assert_eq!(map.get("B"), None);
+
+    match map.entry("c".to_owned()) {
+        linked_hash_map::Entry::Vacant(mut vacant) => {
+            vacant.key_mut().push('c');
+            vacant.insert(3);
+        }
+        linked_hash_map::Entry::Occupied(_) => panic!("expected vacant entry"),
+    }
+    assert_eq!(map.get("cc"), Some(&3));
+
+    let mut entry = map.entry("a".to_owned());
+    assert!(entry.key_mut().is_none());
+    if let linked_hash_map::Entry::Occupied(mut occupied) = entry {
+        assert_eq!(occupied.key_mut(), "a");
+    }
+
+    assert_eq!(
+        map.keys().cloned().collect::<Vec<_>>(),
+        vec!["a", "b", "cc"]
+    );
+}
+
+#[test]
+fn test_retain_count() {
+    let mut map = (0..10).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    assert_eq!(map.retain_count(|&k, _| k % 3 == 0), 6);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![0, 3, 6, 9]);
+    assert_eq!(map.retain_count(|_, _| true), 0);
+    assert_eq!(map.retain_count(|_, _| false), 4);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_default_iterators() {
+    let mut iter = linked_hash_map::Iter::<i32, i32>::default();
+    assert_eq!(iter.len(), 0);
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    let mut iter_mut = linked_hash_map::IterMut::<i32, i32>::default();
+    assert_eq!(iter_mut.next(), None);
+    assert_eq!(iter_mut.next_back(), None);
+
+    assert_eq!(linked_hash_map::Keys::<i32, i32>::default().next(), None);
+    assert_eq!(linked_hash_map::Values::<i32, i32>::default().next(), None);
+    assert_eq!(
+        linked_hash_map::ValuesMut::<i32, i32>::default().next(),
+        None
+    );
+
+    let map = (0..3).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    let mut iter = map.iter();
+    assert_eq!(iter.next(), Some((&0, &0)));
+    let rest = std::mem::take(&mut iter);
+    assert_eq!(rest.count(), 2);
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_insert_before_after() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+    map.insert(3, 30);
+
+    assert_eq!(map.insert_after(&1, 4, 40), Ok(None));
+    assert_eq!(map.insert_before(&1, 5, 50), Ok(None));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![5, 1, 4, 2, 3]);
+
+    assert_eq!(map.insert_after(&3, 5, 51), Ok(Some(50)));
+    assert_eq!(map.insert_before(&2, 3, 31), Ok(Some(30)));
+    assert_eq!(map.insert_after(&4, 2, 21), Ok(Some(20)));
+    assert_eq!(map.insert_before(&1, 1, 11), Ok(Some(10)));
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(1, 11), (4, 40), (2, 21), (3, 31), (5, 51)]
+    );
+    assert_eq!(map.iter().rev().count(), 5);
+
+    assert_eq!(map.insert_after(&6, 7, 70), Err((7, 70)));
+    assert_eq!(map.insert_before(&6, 1, 12), Err((1, 12)));
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.get(&1), Some(&11));
+}
+
+#[test]
+fn test_iter_mut_as_iter() {
+    let mut map = (0..5).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    let mut iter = map.iter_mut();
+    iter.next();
+    iter.next_back();
+
+    assert_eq!(
+        iter.as_iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(1, 1), (2, 2), (3, 3)]
+    );
+    while let Some((_, v)) = iter.next() {
+        let ahead = iter.as_iter().map(|(_, &v)| v).sum::<i32>();
+        *v += ahead;
+    }
+    assert_eq!(
+        map.values().copied().collect::<Vec<_>>(),
+        vec![0, 6, 5, 3, 4]
+    );
+}
+
+#[test]
+fn test_to_vec_into_vec() {
+    let mut map = LinkedHashMap::new();
+    map.insert("b".to_owned(), 2);
+    map.insert("a".to_owned(), 1);
+    map.insert("c".to_owned(), 3);
+    map.to_front("c");
+
+    let expected = vec![
+        ("c".to_owned(), 3),
+        ("b".to_owned(), 2),
+        ("a".to_owned(), 1),
+    ];
+    assert_eq!(map.to_vec(), expected);
+    assert_eq!(map.into_vec(), expected);
+
+    assert!(LinkedHashMap::<i32, i32>::new().into_vec().is_empty());
+}
+
+#[test]
+fn test_extend_map() {
+    let mut a = LinkedHashMap::new();
+    a.insert(1, "a1".to_owned());
+    a.insert(2, "a2".to_owned());
+    a.insert(3, "a3".to_owned());
+
+    let mut b = LinkedHashMap::new();
+    b.insert(4, "b4".to_owned());
+    b.insert(2, "b2".to_owned());
+    b.insert(5, "b5".to_owned());
+    b.remove(&4);
+    b.insert(4, "b4".to_owned());
+
+    a.extend_map(b);
+    assert_eq!(
+        a.iter().map(|(&k, v)| (k, v.as_str())).collect::<Vec<_>>(),
+        vec![(1, "a1"), (3, "a3"), (2, "b2"), (5, "b5"), (4, "b4")]
+    );
+    for k in 1..=5 {
+        assert!(a.contains_key(&k));
+    }
+    assert_eq!(
+        a.iter().rev().map(|(&k, _)| k).collect::<Vec<_>>(),
+        vec![4, 5, 2, 3, 1]
+    );
+
+    a.extend_map(LinkedHashMap::new());
+    assert_eq!(a.len(), 5);
+
+    let mut empty = LinkedHashMap::new();
+    empty.extend_map(a);
+    assert_eq!(
+        empty.keys().copied().collect::<Vec<_>>(),
+        vec![1, 3, 2, 5, 4]
+    );
+    assert_eq!(empty.remove(&2).as_deref(), Some("b2"));
+    empty.insert(6, "6".to_owned());
+    assert_eq!(empty.len(), 5);
+}
+
+#[test]
+fn test_raw_vacant_insert_front() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..3 {
+        match map.raw_entry_mut().from_key(&i) {
+            linked_hash_map::RawEntryMut::Vacant(vacant) => {
+                let (k, v) = vacant.insert_front(i, i * 10);
+                assert_eq!((*k, *v), (i, i * 10));
+            }
+            linked_hash_map::RawEntryMut::Occupied(_) => panic!("expected vacant entry"),
+        }
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 1, 0]);
+    assert_eq!(map.front(), Some((&2, &20)));
+    assert_eq!(map.back(), Some((&0, &0)));
+}
+
+#[test]
+fn test_move_to_index() {
+    let mut map = (0..5).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+
+    let move_to = |map: &mut LinkedHashMap<i32, i32>, k: i32, index: usize| match map.entry(k) {
+        linked_hash_map::Entry::Occupied(mut occupied) => occupied.move_to_index(index),
+        linked_hash_map::Entry::Vacant(_) => panic!("expected occupied entry"),
+    };
+
+    move_to(&mut map, 0, 2);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 0, 3, 4]);
+    move_to(&mut map, 4, 0);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 1, 2, 0, 3]);
+    move_to(&mut map, 1, 10);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 2, 0, 3, 1]);
+    move_to(&mut map, 0, 2);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 2, 0, 3, 1]);
+    assert_eq!(
+        map.iter().rev().map(|(&k, _)| k).collect::<Vec<_>>(),
+        vec![1, 3, 0, 2, 4]
+    );
+}
+
+#[test]
+fn test_retain_keys() {
+    let mut map = LinkedHashMap::new();
+    for i in (0..10).rev() {
+        map.insert(i, i.to_string());
+    }
+    map.retain_keys(|&k| k % 2 == 1);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![9, 7, 5, 3, 1]);
+    assert_eq!(map.get(&5).map(String::as_str), Some("5"));
+}
+
+#[test]
+fn test_split_off_after() {
+    let mut map = (0..6).map(|i| (i, i * 10)).collect::<LinkedHashMap<_, _>>();
+    map.to_back(&2);
+
+    let tail = map.split_off_after(&4).unwrap();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![0, 1, 3, 4]);
+    assert_eq!(tail.keys().copied().collect::<Vec<_>>(), vec![5, 2]);
+    assert_eq!(tail.get(&2), Some(&20));
+    assert_eq!(map.get(&2), None);
+
+    let empty = map.split_off_after(&4).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(map.len(), 4);
+
+    assert!(map.split_off_after(&5).is_none());
+
+    let rest = map.split_off_after(&0).unwrap();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![0]);
+    assert_eq!(
+        rest.iter().rev().map(|(&k, _)| k).collect::<Vec<_>>(),
+        vec![4, 3, 1]
+    );
+}
+
+#[test]
+fn test_map_values_in_place() {
+    let mut map = LinkedHashMap::new();
+    map.insert("c", 3);
+    map.insert("a", 1);
+    map.insert("b", 2);
+
+    let mut seen = Vec::new();
+    map.map_values_in_place(|k, v| {
+        seen.push(*k);
+        *v *= 2;
+    });
+    assert_eq!(seen, vec!["c", "a", "b"]);
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![("c", 6), ("a", 2), ("b", 4)]
+    );
+}
+
+#[test]
+fn test_dedup_by_value() {
+    let mut map = LinkedHashMap::new();
+    for &(k, v) in &[("a", 1), ("b", 1), ("c", 2), ("d", 2), ("e", 1)] {
+        map.insert(k, v);
+    }
+    map.dedup_by_value(|a, b| a == b);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["a", "c", "e"]);
+    assert_eq!(map.get(&"b"), None);
+    assert_eq!(map.iter().rev().count(), 3);
+
+    map.insert("f", 3);
+    map.dedup_by_value(|_, _| true);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["a"]);
+
+    let mut empty: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    empty.dedup_by_value(|_, _| true);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_front_back_key_value() {
+    let mut map = LinkedHashMap::new();
+    assert_eq!(map.front_key(), None);
+    assert_eq!(map.back_value(), None);
+
+    map.insert(1, "a");
+    assert_eq!(map.front_key(), Some(&1));
+    assert_eq!(map.back_key(), Some(&1));
+
+    map.insert(2, "b");
+    map.insert(3, "c");
+    map.to_front(&3);
+    assert_eq!(map.front_key(), Some(&3));
+    assert_eq!(map.front_value(), Some(&"c"));
+    assert_eq!(map.back_key(), Some(&2));
+    assert_eq!(map.back_value(), Some(&"b"));
+}
+
+#[test]
+fn test_push_front() {
+    let mut map = LinkedHashMap::new();
+    assert_eq!(map.push_front("a", 1), None);
+    assert_eq!(map.push_front("b", 2), None);
+    assert_eq!(map.push_front("c", 3), None);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["c", "b", "a"]);
+
+    assert_eq!(map.push_front("a", 10), Some(1));
+    assert_eq!(map.front(), Some((&"a", &10)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["a", "c", "b"]);
+
+    map.insert("d", 4);
+    assert_eq!(map.push_front("e", 5), None);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec!["e", "a", "c", "b", "d"]
+    );
+}
+
+#[test]
+fn test_extend_front() {
+    let mut map = LinkedHashMap::new();
+    map.extend_front(vec![("d", 4), ("e", 5)]);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["d", "e"]);
+
+    map.extend_front(vec![("a", 1), ("b", 2), ("c", 3)]);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec!["a", "b", "c", "d", "e"]
+    );
+
+    // Existing keys are moved into place and updated.
+    map.extend_front(vec![("e", 50), ("c", 30), ("x", 0)]);
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![("e", 50), ("c", 30), ("x", 0), ("a", 1), ("b", 2), ("d", 4)]
+    );
+
+    // Keys already in place, and keys repeated within the batch.
+    map.extend_front(vec![("e", 51), ("c", 31), ("c", 32), ("e", 52)]);
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![("c", 32), ("e", 52), ("x", 0), ("a", 1), ("b", 2), ("d", 4)]
+    );
+    assert_eq!(map.iter().rev().count(), 6);
+
+    map.extend_front(Vec::new());
+    assert_eq!(map.len(), 6);
+}
+
+#[test]
+fn test_position_by_key_and_value() {
+    let mut map = LinkedHashMap::new();
+    assert_eq!(map.position_by_value(|_: &i32| true), None);
+
+    map.insert("a", 1);
+    map.insert("b", 3);
+    map.insert("c", 4);
+    map.insert("d", 6);
+    assert_eq!(map.position_by_value(|v| v % 2 == 0), Some(2));
+    assert_eq!(map.position_by_value(|&v| v > 10), None);
+    assert_eq!(map.position_by_key(|&k| k == "b"), Some(1));
+    assert_eq!(map.position_by_key(|&k| k == "z"), None);
+
+    map.to_front(&"d");
+    assert_eq!(map.position_by_value(|v| v % 2 == 0), Some(0));
+    assert_eq!(map.position_by_key(|&k| k == "b"), Some(2));
+}
+
+#[test]
+fn test_insert_unique_unchecked() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, "a");
+    let (k, v) = map.insert_unique_unchecked(2, "b");
+    assert_eq!(*k, 2);
+    *v = "B";
+    map.insert_unique_unchecked(3, "c");
+    assert_eq!(
+        map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(1, "a"), (2, "B"), (3, "c")]
+    );
+    assert_eq!(map.get(&3), Some(&"c"));
+    assert_eq!(map.back(), Some((&3, &"c")));
+
+    map.remove(&2);
+    map.insert_unique_unchecked(4, "d");
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 3, 4]);
+    check_invariants(&map);
+}
+
+#[test]
+fn test_from_sorted_iter_unchecked() {
+    let entries = (0..100).map(|i| (i, i * 10)).collect::<Vec<_>>();
+    let map: LinkedHashMap<i32, i32> =
+        LinkedHashMap::from_sorted_iter_unchecked(entries.iter().copied());
+    assert_eq!(
+        map,
+        entries.iter().copied().collect::<LinkedHashMap<_, _>>()
+    );
+    assert_eq!(map.get(&42), Some(&420));
+    assert_eq!(map.front(), Some((&0, &0)));
+    assert_eq!(map.back(), Some((&99, &990)));
+    check_invariants(&map);
+
+    let mut map: LinkedHashMap<&str, i32> =
+        LinkedHashMap::from_sorted_iter_unchecked(vec![("b", 1), ("a", 2)]);
+    map.insert("c", 3);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["b", "a", "c"]);
+
+    let empty: LinkedHashMap<i32, i32> = LinkedHashMap::from_sorted_iter_unchecked(Vec::new());
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_peek_front_back() {
+    let mut map = LinkedHashMap::new();
+    assert_eq!(map.peek_front(), None);
+    assert_eq!(map.peek_back(), None);
+
+    map.insert(1, 10);
+    map.insert(2, 20);
+    map.insert(3, 30);
+    assert_eq!(map.peek_front(), Some((&1, &10)));
+    assert_eq!(map.peek_back(), Some((&3, &30)));
+    // Peeking does not reorder anything.
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    map.to_front(&3);
+    assert_eq!(map.peek_front(), Some((&3, &30)));
+    assert_eq!(map.peek_back(), Some((&2, &20)));
+}
+
+#[test]
+fn test_first_last_key_value() {
+    let mut map = LinkedHashMap::new();
+    assert_eq!(map.first_key_value(), None);
+    assert_eq!(map.last_key_value(), None);
+
+    map.insert("a", 1);
+    assert_eq!(map.first_key_value(), Some((&"a", &1)));
+    assert_eq!(map.last_key_value(), Some((&"a", &1)));
+
+    map.insert("b", 2);
+    map.insert("c", 3);
+    map.to_back(&"a");
+    assert_eq!(map.first_key_value(), Some((&"b", &2)));
+    assert_eq!(map.last_key_value(), Some((&"a", &1)));
+    assert_eq!(map.first_key_value(), map.front());
+    assert_eq!(map.last_key_value(), map.back());
+
+    assert_eq!(map.pop_front(), Some(("b", 2)));
+    assert_eq!(map.pop_back(), Some(("a", 1)));
+    assert_eq!(map.first_key_value(), map.last_key_value());
+}
+
+#[test]
+fn test_retain_while() {
+    use std::ops::ControlFlow;
+
+    let mut map = (0..10).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    let mut visited = 0;
+    map.retain_while(|&k, v| {
+        visited += 1;
+        if k >= 4 {
+            ControlFlow::Break(())
+        } else {
+            *v *= 10;
+            ControlFlow::Continue(k % 2 == 1)
+        }
+    });
+    assert_eq!(visited, 5);
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![
+            (1, 10),
+            (3, 30),
+            (4, 4),
+            (5, 5),
+            (6, 6),
+            (7, 7),
+            (8, 8),
+            (9, 9)
+        ]
+    );
+
+    map.retain_while(|_, _| ControlFlow::Continue(false));
+    assert!(map.is_empty());
+    map.insert(1, 1);
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_retain_returning() {
+    let mut map = LinkedHashMap::new();
+    for i in (0..8).rev() {
+        map.insert(i, i * 10);
+    }
+    let removed = map.retain_returning(|&k, v| {
+        *v += 1;
+        k % 3 != 0
+    });
+    assert_eq!(removed, vec![(6, 61), (3, 31), (0, 1)]);
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(7, 71), (5, 51), (4, 41), (2, 21), (1, 11)]
+    );
+    assert!(map.retain_returning(|_, _| true).is_empty());
+}
+
+#[test]
+fn test_remove_entry() {
+    let mut map = LinkedHashMap::new();
+    map.insert("a".to_owned(), 1);
+    map.insert("b".to_owned(), 2);
+    map.insert("c".to_owned(), 3);
+
+    let (key, value) = map.remove_entry("b").unwrap();
+    assert_eq!((key.as_str(), value), ("b", 2));
+    assert_eq!(map.remove_entry("b"), None);
+    assert_eq!(map.keys().cloned().collect::<Vec<_>>(), vec!["a", "c"]);
+
+    // The recovered key can be reused.
+    map.insert(key, 4);
+    assert_eq!(map.back(), Some((&"b".to_owned(), &4)));
+}
+
+#[test]
+fn test_iter_rev() {
+    let map = (0..5).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    assert_eq!(
+        map.iter_rev().map(|(&k, _)| k).collect::<Vec<_>>(),
+        vec![4, 3, 2, 1, 0]
+    );
+    assert_eq!(map.iter_rev().len(), 5);
+}
+
+// Consumes `iter` from both ends following `pattern`, checking every item and the reported length
+// against the expected sequence.
+fn check_double_ended<I>(mut iter: I, expected: Vec<I::Item>, pattern: u32)
+where
+    I: DoubleEndedIterator + ExactSizeIterator,
+    I::Item: PartialEq + std::fmt::Debug,
+{
+    let mut expected = std::collections::VecDeque::from(expected);
+    for step in 0.. {
+        assert_eq!(iter.len(), expected.len());
+        assert_eq!(iter.size_hint(), (expected.len(), Some(expected.len())));
+        let (item, expected_item) = if pattern & (1 << (step % 32)) == 0 {
+            (iter.next(), expected.pop_front())
+        } else {
+            (iter.next_back(), expected.pop_back())
+        };
+        assert_eq!(item, expected_item);
+        if item.is_none() {
+            break;
+        }
+    }
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn test_double_ended_iterators() {
+    let make = || {
+        let mut map = LinkedHashMap::new();
+        for i in 0..12 {
+            map.insert(i, i * 10);
+        }
+        map.to_front(&7);
+        map.remove(&3);
+        map
+    };
+    let entries = make().into_iter().collect::<Vec<_>>();
+    let keys = entries.iter().map(|&(k, _)| k).collect::<Vec<_>>();
+    let values = entries.iter().map(|&(_, v)| v).collect::<Vec<_>>();
+
+    let mut seed = 0x2545_f491u32;
+    for _ in 0..32 {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        let pattern = seed;
+
+        let mut map = make();
+        check_double_ended(map.iter().map(|(&k, &v)| (k, v)), entries.clone(), pattern);
+        check_double_ended(
+            map.iter().rev().map(|(&k, &v)| (k, v)),
+            entries.iter().rev().copied().collect(),
+            pattern,
+        );
+        check_double_ended(map.keys().copied(), keys.clone(), pattern);
+        check_double_ended(map.values().copied(), values.clone(), pattern);
+        check_double_ended(
+            map.iter_mut().map(|(&k, v)| (k, *v)),
+            entries.clone(),
+            pattern,
+        );
+        check_double_ended(map.values_mut().map(|v| *v), values.clone(), pattern);
+        check_double_ended(map.drain(), entries.clone(), pattern);
+        check_double_ended(make().into_iter(), entries.clone(), pattern);
+    }
+}
+
+#[test]
+fn test_or_insert_checked() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+
+    let (v, inserted) = map.entry(3).or_insert_checked(30);
+    assert!(inserted);
+    *v += 1;
+
+    let (v, inserted) = map.entry(1).or_insert_checked(100);
+    assert!(!inserted);
+    assert_eq!(*v, 10);
+
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(2, 20), (3, 31), (1, 10)]
+    );
+}
+
+#[test]
+fn test_iter_nth() {
+    let mut map = (0..6).map(|i| (i, i * 10)).collect::<LinkedHashMap<_, _>>();
+
+    assert_eq!(map.iter().nth(3), Some((&3, &30)));
+    assert_eq!(map.iter().nth_back(1), Some((&4, &40)));
+    assert_eq!(map.iter().nth(6), None);
+
+    let mut iter = map.iter();
+    assert_eq!(iter.nth(1), Some((&1, &10)));
+    assert_eq!(iter.nth_back(2), Some((&3, &30)));
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next(), Some((&2, &20)));
+    assert_eq!(iter.nth(1), None);
+
+    let mut iter = map.iter();
+    assert_eq!(iter.nth(10), None);
+    assert_eq!(iter.next_back(), None);
+
+    assert_eq!(map.keys().nth(2), Some(&2));
+    assert_eq!(map.keys().nth_back(2), Some(&3));
+    assert_eq!(map.values().nth(5), Some(&50));
+    assert_eq!(map.values().nth_back(5), Some(&0));
+
+    let mut iter = map.iter_mut();
+    *iter.nth(2).unwrap().1 += 1;
+    *iter.nth_back(1).unwrap().1 += 1;
+    assert_eq!(iter.len(), 1);
+    *map.values_mut().nth(5).unwrap() += 1;
+    *map.values_mut().nth_back(5).unwrap() += 1;
+    assert_eq!(
+        map.values().copied().collect::<Vec<_>>(),
+        vec![1, 10, 21, 30, 41, 51]
+    );
+}
+
+#[test]
+fn test_get_or_insert_with_key() {
+    let mut map = LinkedHashMap::new();
+    let mut calls = 0;
+    let mut len_of = |k: &String| {
+        calls += 1;
+        k.len()
+    };
+
+    assert_eq!(
+        *map.get_or_insert_with_key("abc".to_owned(), &mut len_of),
+        3
+    );
+    assert_eq!(*map.get_or_insert_with_key("de".to_owned(), &mut len_of), 2);
+    *map.get_or_insert_with_key("abc".to_owned(), &mut len_of) += 10;
+    assert_eq!(calls, 2);
+
+    assert_eq!(
+        map.iter()
+            .map(|(k, &v)| (k.as_str(), v))
+            .collect::<Vec<_>>(),
+        vec![("de", 2), ("abc", 13)]
+    );
+}
+
+#[test]
+fn test_check_invariants_after_mutations() {
+    let mut map = LinkedHashMap::new();
+    check_invariants(&map);
+    for i in 0..20 {
+        map.insert(i, i);
+        check_invariants(&map);
+    }
+    for i in (0..20).step_by(3) {
+        map.remove(&i);
+        map.to_front(&(i + 1));
+        check_invariants(&map);
+    }
+    map.retain(|&k, _| k % 2 == 0);
+    check_invariants(&map);
+    map.drain().next();
+    check_invariants(&map);
+}
+
+#[test]
+fn test_into_vec_from() {
+    let mut map = LinkedHashMap::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    map.insert(3, "c");
+
+    let entries: Vec<(i32, &str)> = map.into();
+    assert_eq!(entries, vec![(2, "b"), (1, "a"), (3, "c")]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_into_hash_map() {
+    use std::collections::HashMap;
+
+    let map = (0..10)
+        .map(|i| (i, i * 10))
+        .collect::<LinkedHashMap<_, _>>();
+    let hash_map = HashMap::from(map);
+    assert_eq!(hash_map.len(), 10);
+    for i in 0..10 {
+        assert_eq!(hash_map[&i], i * 10);
+    }
+}
+
+#[test]
+fn test_entry_no_promote() {
+    let mut map = (0..4).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+
+    *map.entry_no_promote(1).or_insert(100) += 10;
+    map.entry_no_promote(2)
+        .and_modify(|v| *v += 20)
+        .or_insert(200);
+    map.entry_no_promote(0).insert(1);
+    if let linked_hash_map::Entry::Occupied(mut occupied) = map.entry_no_promote(3) {
+        assert_eq!(occupied.insert(33), 3);
+    }
+    assert!(map.entry_no_promote(4).or_insert_checked(4).1);
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(0, 1), (1, 11), (2, 22), (3, 33), (4, 4)]
+    );
+
+    // Explicit moves still apply.
+    if let linked_hash_map::Entry::Occupied(mut occupied) = map.entry_no_promote(0) {
+        occupied.to_back();
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 0]);
+
+    // Whereas `entry` moves occupied entries to the back.
+    map.entry(1).or_insert(0);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 3, 4, 0, 1]);
+}
+
+#[test]
+fn test_drain_filter() {
+    let mut map = LinkedHashMap::new();
+    for i in (0..10).rev() {
+        map.insert(i, i);
+    }
+    map.to_front(&4);
+    let capacity = map.capacity();
+
+    let mut visited = Vec::new();
+    let extracted = map
+        .drain_filter(|&k, v| {
+            visited.push(k);
+            *v *= 10;
+            k % 2 == 0
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(visited, vec![4, 9, 8, 7, 6, 5, 3, 2, 1, 0]);
+    assert_eq!(extracted, vec![(4, 40), (8, 80), (6, 60), (2, 20), (0, 0)]);
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        vec![(9, 90), (7, 70), (5, 50), (3, 30), (1, 10)]
+    );
+    assert_eq!(map.capacity(), capacity);
+
+    // Dropping the iterator early still visits and removes the rest.
+    let mut visited = Vec::new();
+    let mut iter = map.drain_filter(|&k, _| {
+        visited.push(k);
+        k > 2
+    });
+    assert_eq!(iter.next(), Some((9, 90)));
+    drop(iter);
+    assert_eq!(visited, vec![9, 7, 5, 3, 1]);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1]);
+    assert_eq!(map.capacity(), capacity);
+
+    map.insert(11, 11);
+    assert_eq!(map.drain_filter(|_, _| false).count(), 0);
+    assert_eq!(
+        format!("{:?}", map.drain_filter(|_, _| false)),
+        "DrainFilter"
+    );
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_drain_filter_panic() {
+    let mut map = (0..6).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.drain_filter(|&k, _| {
+            if k == 3 {
+                panic!("predicate panicked");
+            }
+            k % 2 == 0
+        })
+        .for_each(drop);
+    }));
+    assert!(result.is_err());
+    check_invariants(&map);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5]);
+}
+
+#[test]
+fn test_range_positions() {
+    let map = (0..10)
+        .map(|i| (i, i * 10))
+        .collect::<LinkedHashMap<_, _>>();
+
+    assert_eq!(
+        map.range_positions(2, 5).collect::<Vec<_>>(),
+        vec![(&2, &20), (&3, &30), (&4, &40)]
+    );
+    assert_eq!(
+        map.range_positions(7, 10)
+            .rev()
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>(),
+        vec![9, 8, 7]
+    );
+    assert_eq!(map.range_positions(8, 100).len(), 2);
+    assert_eq!(map.range_positions(0, 100).count(), 10);
+
+    assert_eq!(map.range_positions(4, 4).next(), None);
+    assert_eq!(map.range_positions(6, 3).next(), None);
+    assert_eq!(map.range_positions(10, 20).next(), None);
+    assert_eq!(map.range_positions(20, 30).next_back(), None);
+
+    let empty: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    assert_eq!(empty.range_positions(0, 1).next(), None);
 }
@@ -133,6 +133,23 @@ fn test_subset_and_superset() {
     assert!(b.is_superset(&a));
 }
 
+#[test]
+fn test_subset_disjoint_empty() {
+    let empty: LinkedHashSet<i32> = LinkedHashSet::new();
+    let a: LinkedHashSet<i32> = vec![1, 2, 3].into_iter().collect();
+
+    assert!(empty.is_subset(&a));
+    assert!(empty.is_subset(&empty));
+    assert!(!a.is_subset(&empty));
+    assert!(a.is_superset(&empty));
+    assert!(a.is_subset(&a.clone()));
+    assert!(a.is_superset(&a.clone()));
+
+    assert!(empty.is_disjoint(&a));
+    assert!(a.is_disjoint(&empty));
+    assert!(!a.is_disjoint(&a));
+}
+
 #[test]
 fn test_iterate() {
     let mut a = LinkedHashSet::new();
@@ -421,6 +438,12 @@ fn test_retain() {
     assert!(set.contains(&2));
     assert!(set.contains(&4));
     assert!(set.contains(&6));
+
+    let mut set: LinkedHashSet<i32> = vec![5, 8, 1, 4, 7, 2].into_iter().collect();
+    set.retain(|&k| k % 2 == 0);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![8, 4, 2]);
+    assert_eq!(set.drain().collect::<Vec<_>>(), vec![8, 4, 2]);
+    assert!(set.is_empty());
 }
 
 #[test]
@@ -541,3 +564,166 @@ fn test_order_equality() {
     set2.to_front(&4);
     assert_eq!(set1, set2);
 }
+
+#[test]
+fn test_set_operations_order() {
+    let a: LinkedHashSet<i32> = vec![5, 1, 4, 2].into_iter().collect();
+    let b: LinkedHashSet<i32> = vec![3, 2, 6, 5].into_iter().collect();
+
+    assert_eq!(
+        a.union(&b).copied().collect::<Vec<_>>(),
+        vec![5, 1, 4, 2, 3, 6]
+    );
+    assert_eq!(a.intersection(&b).copied().collect::<Vec<_>>(), vec![5, 2]);
+    assert_eq!(b.intersection(&a).copied().collect::<Vec<_>>(), vec![2, 5]);
+    assert_eq!(a.difference(&b).copied().collect::<Vec<_>>(), vec![1, 4]);
+    assert_eq!(
+        a.symmetric_difference(&b).copied().collect::<Vec<_>>(),
+        vec![1, 4, 3, 6]
+    );
+}
+
+#[test]
+fn test_rev_iterators() {
+    let mut a: LinkedHashSet<i32> = vec![1, 2, 3, 4].into_iter().collect();
+    let b: LinkedHashSet<i32> = vec![6, 4, 5, 2].into_iter().collect();
+
+    assert_eq!(
+        a.iter().rev().copied().collect::<Vec<_>>(),
+        vec![4, 3, 2, 1]
+    );
+    assert_eq!(
+        a.union(&b).rev().copied().collect::<Vec<_>>(),
+        vec![5, 6, 4, 3, 2, 1]
+    );
+    assert_eq!(
+        a.intersection(&b).rev().copied().collect::<Vec<_>>(),
+        vec![4, 2]
+    );
+    assert_eq!(
+        a.difference(&b).rev().copied().collect::<Vec<_>>(),
+        vec![3, 1]
+    );
+    assert_eq!(
+        a.symmetric_difference(&b)
+            .rev()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![5, 6, 3, 1]
+    );
+    assert_eq!(
+        a.clone().into_iter().rev().collect::<Vec<_>>(),
+        vec![4, 3, 2, 1]
+    );
+    assert_eq!(a.drain().rev().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+}
+
+#[test]
+fn test_to_back_front_iteration_order() {
+    let mut set: LinkedHashSet<i32> = (1..=5).collect();
+
+    assert!(set.to_back(&2));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5, 2]);
+    assert!(set.to_front(&4));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![4, 1, 3, 5, 2]);
+
+    assert!(!set.to_back(&6));
+    assert!(!set.to_front(&6));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![4, 1, 3, 5, 2]);
+}
+
+#[test]
+fn test_capacity() {
+    let mut s = LinkedHashSet::with_capacity(100);
+    assert!(s.capacity() >= 100);
+    for i in 0..100 {
+        s.insert(i);
+    }
+    assert!(s.capacity() >= 100);
+
+    let mut s: LinkedHashSet<i32> =
+        LinkedHashSet::with_capacity_and_hasher(10, DefaultHashBuilder::default());
+    assert!(s.capacity() >= 10);
+    s.reserve(50);
+    assert!(s.capacity() >= 50);
+    s.insert(1);
+    s.shrink_to_fit();
+    assert!(s.capacity() >= 1 && s.capacity() < 50);
+
+    // Like the map, constructors do not require the value type to be hashable.
+    struct NotHash;
+    let s: LinkedHashSet<NotHash> = LinkedHashSet::with_capacity(4);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn test_take() {
+    use std::borrow::Borrow;
+    use std::hash::{Hash, Hasher};
+
+    #[derive(Debug)]
+    struct Interned {
+        name: String,
+        id: u32,
+    }
+
+    impl PartialEq for Interned {
+        fn eq(&self, other: &Self) -> bool {
+            self.name == other.name
+        }
+    }
+
+    impl Eq for Interned {}
+
+    impl Hash for Interned {
+        fn hash<H: Hasher>(&self, h: &mut H) {
+            self.name.hash(h)
+        }
+    }
+
+    impl Borrow<str> for Interned {
+        fn borrow(&self) -> &str {
+            &self.name
+        }
+    }
+
+    let mut set = LinkedHashSet::new();
+    for (id, name) in ["a", "b", "c"].iter().enumerate() {
+        set.insert(Interned {
+            name: name.to_string(),
+            id: id as u32,
+        });
+    }
+
+    let taken = set.take("b").unwrap();
+    assert_eq!((taken.name.as_str(), taken.id), ("b", 1));
+    assert!(set.take("b").is_none());
+    assert_eq!(set.iter().map(|i| i.id).collect::<Vec<_>>(), vec![0, 2]);
+}
+
+#[test]
+fn test_trait_parity() {
+    let values = [5, 3, 9, 1, 3, 7];
+    // Re-inserting a value moves it to the back, like the map's `insert`.
+    let set: LinkedHashSet<i32> = values.iter().copied().collect();
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![5, 9, 1, 3, 7]);
+    assert_eq!((&set).into_iter().count(), 5);
+    assert_eq!(
+        set.clone().into_iter().collect::<Vec<_>>(),
+        vec![5, 9, 1, 3, 7]
+    );
+
+    let mut other = LinkedHashSet::default();
+    other.extend(set.iter());
+    assert_eq!(other, set);
+    assert_eq!(format!("{:?}", other), "{5, 9, 1, 3, 7}");
+
+    other.to_back(&9);
+    assert_ne!(other, set);
+    assert!(other < set);
+    assert_eq!(set.cmp(&other), std::cmp::Ordering::Greater);
+    assert_eq!(
+        set.partial_cmp(&set.clone()),
+        Some(std::cmp::Ordering::Equal)
+    );
+}
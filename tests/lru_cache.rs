@@ -1,4 +1,4 @@
-use hashlink::LruCache;
+use hashlink::{lru_cache::LruCacheBuilder, LruCache};
 
 #[test]
 fn test_put_and_get() {
@@ -164,3 +164,323 @@ fn test_entry() {
 
     assert!(cache.len() <= 5);
 }
+
+#[test]
+fn test_get_or_insert_with() {
+    let mut cache = LruCache::new(2);
+    assert_eq!(*cache.get_or_insert_with(1, || 10), 10);
+    assert_eq!(*cache.get_or_insert_with(2, || 20), 20);
+    assert_eq!(*cache.get_or_insert_with(1, || unreachable!()), 10);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+
+    *cache.get_or_insert_with(3, || 30) += 1;
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.peek(&2), None);
+    assert_eq!(cache.peek(&3), Some(&31));
+
+    let mut cache = LruCache::new(0);
+    assert_eq!(*cache.get_or_insert_with(1, || 10), 10);
+    assert_eq!(*cache.get_or_insert_with(2, || 20), 20);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.peek(&2), Some(&20));
+}
+
+#[test]
+fn test_get_or_insert_with_panic() {
+    let mut cache = LruCache::new(2);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        cache.get_or_insert_with(3, || panic!("boom"));
+    }));
+    assert!(res.is_err());
+    assert_eq!(
+        cache.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(1, 10), (2, 20)]
+    );
+}
+
+#[test]
+fn test_get_or_insert_with_over_capacity() {
+    let mut cache = LruCache::new(2);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.entry(3).or_insert(30);
+    assert_eq!(cache.len(), 3);
+
+    assert_eq!(*cache.get_or_insert_with(4, || 40), 40);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+}
+
+#[test]
+fn test_pop_lru() {
+    let mut cache = LruCache::new(2);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.get(&1);
+    assert_eq!(cache.pop_lru(), Some((2, 20)));
+    assert_eq!(cache.pop_lru(), Some((1, 10)));
+    assert_eq!(cache.pop_lru(), None);
+}
+
+#[test]
+fn test_peek_lru_and_contains() {
+    let mut cache = LruCache::new(3);
+    assert_eq!(cache.peek_lru(), None);
+
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+
+    assert_eq!(cache.peek_lru(), Some((&1, &10)));
+    assert!(cache.contains(&1));
+    assert!(!cache.contains(&4));
+    assert_eq!(cache.peek_lru(), Some((&1, &10)));
+
+    cache.insert(4, 40);
+    assert!(!cache.contains(&1));
+    assert_eq!(cache.peek_lru(), Some((&2, &20)));
+}
+
+#[test]
+fn test_iter_recency_order() {
+    let mut cache = LruCache::new(3);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    cache.get(&1);
+
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![2, 3, 1]
+    );
+    assert_eq!(
+        cache.iter_mru().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![1, 3, 2]
+    );
+    // Iterating does not promote anything.
+    assert_eq!(cache.peek_lru(), Some((&2, &20)));
+}
+
+#[test]
+fn test_set_capacity_both_directions() {
+    let mut cache = LruCache::new(4);
+    for i in 0..4 {
+        cache.insert(i, i);
+    }
+    cache.get(&0);
+    cache.get(&2);
+
+    cache.set_capacity(6);
+    assert_eq!(cache.len(), 4);
+    cache.insert(4, 4);
+    cache.insert(5, 5);
+    assert_eq!(cache.len(), 6);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![1, 3, 0, 2, 4, 5]
+    );
+
+    cache.set_capacity(3);
+    assert_eq!(cache.capacity(), 3);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![2, 4, 5]
+    );
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.capacity(), 3);
+    cache.insert(6, 6);
+    assert_eq!(cache.peek_lru(), Some((&6, &6)));
+}
+
+#[test]
+fn test_get_or_insert() {
+    let mut cache = LruCache::new(2);
+    assert_eq!(*cache.get_or_insert(1, 10), 10);
+    assert_eq!(*cache.get_or_insert(2, 20), 20);
+    assert_eq!(cache.iter_mru().next(), Some((&2, &20)));
+
+    *cache.get_or_insert(1, 11) += 1;
+    assert_eq!(cache.iter_mru().next(), Some((&1, &11)));
+    assert_eq!(*cache.get_or_insert(1, 12), 11);
+    assert_eq!(cache.iter_mru().next(), Some((&1, &11)));
+
+    assert_eq!(*cache.get_or_insert(3, 30), 30);
+    assert!(!cache.contains(&2));
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+}
+
+#[test]
+fn test_get_mut_promotes() {
+    let mut cache = LruCache::new(3);
+    cache.insert("a", vec![1]);
+    cache.insert("b", vec![2]);
+    cache.insert("c", vec![3]);
+
+    cache.get_mut("a").unwrap().push(10);
+    assert_eq!(cache.iter_mru().next(), Some((&"a", &vec![1, 10])));
+    assert_eq!(cache.peek_lru(), Some((&"b", &vec![2])));
+
+    cache.insert("d", vec![4]);
+    assert!(!cache.contains("b"));
+    assert_eq!(cache.peek("a"), Some(&vec![1, 10]));
+}
+
+#[test]
+fn test_demote() {
+    let mut cache = LruCache::new(3);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    cache.insert(3, 3);
+
+    assert!(cache.demote(&3));
+    assert!(!cache.demote(&4));
+    assert_eq!(cache.peek_lru(), Some((&3, &3)));
+
+    cache.insert(4, 4);
+    assert!(!cache.contains(&3));
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![1, 2, 4]
+    );
+}
+
+#[test]
+fn test_builder_promote_on_get() {
+    let mut cache = LruCacheBuilder::new(3).build();
+    assert!(cache.promotes_on_get());
+    cache.insert(1, "a");
+    cache.insert(2, "b");
+    cache.insert(3, "c");
+    cache.get(&1);
+    cache.insert(4, "d");
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![3, 1, 4]
+    );
+
+    let mut cache = LruCacheBuilder::new(3).promote_on_get(false).build();
+    assert!(!cache.promotes_on_get());
+    cache.insert(1, "a");
+    cache.insert(2, "b");
+    cache.insert(3, "c");
+    assert_eq!(cache.get(&1), Some(&"a"));
+    *cache.get_mut(&2).unwrap() = "B";
+    assert!(cache.contains_key(&1));
+    assert_eq!(*cache.get_or_insert_with(1, || "z"), "a");
+    assert_eq!(*cache.entry(1).or_insert("z"), "a");
+    cache.entry(2).and_modify(|v| *v = "b").or_insert("z");
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    cache.insert(4, "d");
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![2, 3, 4]
+    );
+    cache.insert(5, "e");
+    assert_eq!(
+        cache.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(3, "c"), (4, "d"), (5, "e")]
+    );
+
+    let cloned = cache.clone();
+    assert!(!cloned.promotes_on_get());
+
+    // Misses still evict in insertion order.
+    assert_eq!(*cache.get_or_insert_with(6, || "f"), "f");
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![4, 5, 6]
+    );
+}
+
+#[test]
+fn test_builder_options() {
+    let mut cache = LruCacheBuilder::new(1)
+        .capacity(2)
+        .with_hasher(hashbrown::hash_map::DefaultHashBuilder::default())
+        .reserve_nodes(2)
+        .build();
+    assert_eq!(cache.capacity(), 2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+    cache.insert("c", 3);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.peek_lru(), Some((&"b", &2)));
+}
+
+#[test]
+fn test_is_full_and_utilization() {
+    let mut cache = LruCache::new(4);
+    assert!(!cache.is_full());
+    assert_eq!(cache.utilization(), 0.0);
+
+    cache.insert(1, 1);
+    assert_eq!(cache.utilization(), 0.25);
+    cache.insert(2, 2);
+    cache.insert(3, 3);
+    assert!(!cache.is_full());
+    cache.insert(4, 4);
+    assert!(cache.is_full());
+    assert_eq!(cache.utilization(), 1.0);
+    assert_eq!(cache.peek_lru(), Some((&1, &1)));
+
+    cache.insert(5, 5);
+    assert!(cache.is_full());
+    cache.set_capacity(8);
+    assert!(!cache.is_full());
+    assert_eq!(cache.utilization(), 0.5);
+
+    let empty: LruCache<i32, i32> = LruCache::new(0);
+    assert!(empty.is_full());
+    assert_eq!(empty.utilization(), 1.0);
+
+    let unbounded: LruCache<i32, i32> = LruCache::new_unbounded();
+    assert!(!unbounded.is_full());
+    assert_eq!(unbounded.utilization(), 0.0);
+}
+
+#[test]
+fn test_retain() {
+    let mut cache = LruCache::new(5);
+    cache.insert(("alice", 1), 1);
+    cache.insert(("bob", 1), 2);
+    cache.insert(("alice", 2), 3);
+    cache.insert(("carol", 1), 4);
+    cache.insert(("bob", 2), 5);
+    cache.get(&("bob", 1));
+
+    cache.retain(|&(user, _), _| user != "alice");
+    assert_eq!(cache.len(), 3);
+    assert!(!cache.contains(&("alice", 1)));
+    assert!(!cache.contains(&("alice", 2)));
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![("carol", 1), ("bob", 2), ("bob", 1)]
+    );
+
+    // Survivors can be updated in place, and removed entries free up room.
+    cache.retain(|_, v| {
+        *v *= 10;
+        true
+    });
+    assert_eq!(cache.peek(&("bob", 2)), Some(&50));
+    cache.insert(("dave", 1), 6);
+    cache.insert(("dave", 2), 7);
+    assert_eq!(cache.len(), 5);
+    assert_eq!(cache.peek_lru(), Some((&("carol", 1), &40)));
+}
@@ -2,9 +2,10 @@
 
 use std::hash::BuildHasherDefault;
 
-use hashlink::{LinkedHashMap, LinkedHashSet};
+use hashlink::{lru_cache::LruCacheBuilder, LinkedHashMap, LinkedHashSet, LruCache};
 use rustc_hash::FxHasher;
-use serde_test::{assert_tokens, Token};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_test::{assert_de_tokens, assert_tokens, Token};
 
 #[test]
 fn map_serde_tokens_empty() {
@@ -108,3 +109,103 @@ fn set_serde_tokens_generic() {
         ],
     );
 }
+
+// `LruCache` does not implement `PartialEq`, so compare its configuration and entries in order.
+#[derive(Debug)]
+struct Lru(LruCache<char, i32>);
+
+impl PartialEq for Lru {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.capacity() == other.0.capacity()
+            && self.0.promotes_on_get() == other.0.promotes_on_get()
+            && self.0.iter().eq(other.0.iter())
+    }
+}
+
+impl Serialize for Lru {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lru {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LruCache::deserialize(deserializer).map(Lru)
+    }
+}
+
+#[test]
+fn lru_serde_tokens() {
+    let mut cache = LruCache::new(3);
+    cache.insert('a', 10);
+    cache.insert('b', 20);
+    cache.insert('c', 30);
+    cache.get(&'a');
+
+    assert_tokens(
+        &Lru(cache),
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(3),
+            Token::Bool(true),
+            Token::Map { len: Some(3) },
+            Token::Char('b'),
+            Token::I32(20),
+            Token::Char('c'),
+            Token::I32(30),
+            Token::Char('a'),
+            Token::I32(10),
+            Token::MapEnd,
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[test]
+fn lru_serde_tokens_over_capacity() {
+    let mut cache = LruCache::new(2);
+    cache.insert('b', 20);
+    cache.insert('c', 30);
+
+    assert_de_tokens(
+        &Lru(cache),
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(2),
+            Token::Bool(true),
+            Token::Map { len: Some(3) },
+            Token::Char('a'),
+            Token::I32(10),
+            Token::Char('b'),
+            Token::I32(20),
+            Token::Char('c'),
+            Token::I32(30),
+            Token::MapEnd,
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[test]
+fn lru_serde_tokens_fifo() {
+    let mut cache = LruCacheBuilder::new(2).promote_on_get(false).build();
+    cache.insert('a', 10);
+    cache.insert('b', 20);
+    cache.get(&'a');
+
+    assert_tokens(
+        &Lru(cache),
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(2),
+            Token::Bool(false),
+            Token::Map { len: Some(2) },
+            Token::Char('a'),
+            Token::I32(10),
+            Token::Char('b'),
+            Token::I32(20),
+            Token::MapEnd,
+            Token::TupleEnd,
+        ],
+    );
+}
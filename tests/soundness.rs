@@ -0,0 +1,268 @@
+// Edge cases for the guard node, the free list and the linking of nodes, meant to be run under
+// Miri and the sanitizers as well as natively.
+
+use std::{
+    cell::Cell,
+    hash::Hash,
+    ops::ControlFlow,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use hashlink::{linked_hash_map::RawEntryMut, LinkedHashMap};
+
+// A value which counts how many times it has been dropped.
+struct DropCounter(Rc<Cell<usize>>);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn check_links<K: Copy + Hash + Eq + std::fmt::Debug, V>(map: &LinkedHashMap<K, V>) {
+    #[cfg(debug_assertions)]
+    map.check_invariants();
+    let forward = map.keys().copied().collect::<Vec<_>>();
+    let mut backward = map.keys().rev().copied().collect::<Vec<_>>();
+    backward.reverse();
+    assert_eq!(forward, backward);
+    assert_eq!(forward.len(), map.len());
+    assert_eq!(map.front().map(|(k, _)| *k), forward.first().copied());
+    assert_eq!(map.back().map(|(k, _)| *k), forward.last().copied());
+}
+
+#[test]
+fn test_empty_map() {
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    map.clear();
+    map.clear_retaining_capacity();
+    map.shrink_to_fit();
+    assert_eq!(map.pop_front(), None);
+    assert_eq!(map.pop_back(), None);
+    assert_eq!(map.drain().next(), None);
+    assert_eq!(map.iter().next_back(), None);
+    assert_eq!(map.to_back(&1), None);
+    map.retain(|_, _| false);
+    check_links(&map);
+    assert_eq!(map.into_iter().next(), None);
+}
+
+#[test]
+fn test_single_element() {
+    let mut map = LinkedHashMap::new();
+    map.insert(1, 1);
+    check_links(&map);
+
+    assert!(map.to_front(&1).is_some());
+    assert!(map.to_back(&1).is_some());
+    check_links(&map);
+
+    assert_eq!(map.pop_front(), Some((1, 1)));
+    check_links(&map);
+    map.insert(2, 2);
+    assert_eq!(map.pop_back(), Some((2, 2)));
+    check_links(&map);
+
+    map.insert(3, 3);
+    assert_eq!(map.remove(&3), Some(3));
+    check_links(&map);
+    map.insert(4, 4);
+    check_links(&map);
+}
+
+#[test]
+fn test_drain_then_reuse() {
+    let drops = Rc::new(Cell::new(0));
+    let mut map = LinkedHashMap::new();
+    for i in 0..10 {
+        map.insert(i, DropCounter(drops.clone()));
+    }
+
+    {
+        let mut drain = map.drain();
+        drain.next();
+        drain.next_back();
+    }
+    assert_eq!(drops.get(), 10);
+    check_links(&map);
+
+    for i in 0..20 {
+        map.insert(i, DropCounter(drops.clone()));
+    }
+    check_links(&map);
+    map.retain(|&k, _| k % 3 == 0);
+    assert_eq!(drops.get(), 23);
+    check_links(&map);
+
+    map.shrink_to_fit();
+    drop(map);
+    assert_eq!(drops.get(), 30);
+}
+
+#[test]
+fn test_free_list_reuse() {
+    let drops = Rc::new(Cell::new(0));
+    let mut map = LinkedHashMap::new();
+    for round in 0..5 {
+        for i in 0..8 {
+            map.insert(i, DropCounter(drops.clone()));
+        }
+        for i in (0..8).step_by(2) {
+            assert!(map.remove(&i).is_some());
+        }
+        check_links(&map);
+        map.clear_retaining_capacity();
+        assert_eq!(drops.get(), (round + 1) * 8);
+    }
+
+    map.insert(0, DropCounter(drops.clone()));
+    map.shrink_free_list(1);
+    map.insert(1, DropCounter(drops.clone()));
+    map.insert(2, DropCounter(drops.clone()));
+    check_links(&map);
+    drop(map);
+    assert_eq!(drops.get(), 43);
+}
+
+#[test]
+fn test_interleaved_raw_entries() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..16 {
+        match map.raw_entry_mut().from_key(&i) {
+            RawEntryMut::Vacant(vacant) => {
+                if i % 2 == 0 {
+                    vacant.insert(i, i);
+                } else {
+                    vacant.insert_front(i, i);
+                }
+            }
+            RawEntryMut::Occupied(_) => panic!("unexpected occupied entry"),
+        }
+        if i % 3 == 0 {
+            match map.raw_entry_mut().from_key(&(i / 2)) {
+                RawEntryMut::Occupied(occupied) => {
+                    occupied.remove();
+                }
+                RawEntryMut::Vacant(_) => {}
+            }
+        }
+        check_links(&map);
+    }
+
+    while let Some((k, _)) = map.pop_front() {
+        if let RawEntryMut::Occupied(mut occupied) = map.raw_entry_mut().from_key(&(k + 1)) {
+            occupied.to_front();
+        }
+        check_links(&map);
+    }
+}
+
+#[test]
+fn test_retain_panic() {
+    let drops = Rc::new(Cell::new(0));
+    let mut map = LinkedHashMap::new();
+    for i in 0..10 {
+        map.insert(i, DropCounter(drops.clone()));
+    }
+    let order = map.keys().copied().collect::<Vec<_>>();
+
+    let mut visited = Vec::new();
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        map.retain(|&k, _| {
+            if visited.len() == 2 {
+                panic!("predicate panicked");
+            }
+            visited.push(k);
+            false
+        })
+    }));
+    assert!(res.is_err());
+
+    // The two rejected entries were removed and dropped, the rest keep their order.
+    assert_eq!(drops.get(), 2);
+    assert_eq!(map.len(), 8);
+    check_links(&map);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        order
+            .iter()
+            .copied()
+            .filter(|k| !visited.contains(k))
+            .collect::<Vec<_>>()
+    );
+
+    // The nodes of the removed entries are reused.
+    map.insert(10, DropCounter(drops.clone()));
+    map.insert(11, DropCounter(drops.clone()));
+    check_links(&map);
+    assert_eq!(map.iter().count(), 10);
+    drop(map);
+    assert_eq!(drops.get(), 12);
+}
+
+#[test]
+fn test_into_iter_partial() {
+    let drops = Rc::new(Cell::new(0));
+    let mut map = LinkedHashMap::new();
+    for i in 0..6 {
+        map.insert(i, DropCounter(drops.clone()));
+    }
+    map.remove(&2);
+
+    let mut iter = map.into_iter();
+    assert_eq!(iter.next().map(|(k, _)| k), Some(0));
+    assert_eq!(iter.next_back().map(|(k, _)| k), Some(5));
+    drop(iter);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_node_moving_operations() {
+    let mut map = (0..32).map(|i| (i, i)).collect::<LinkedHashMap<_, _>>();
+    check_links(&map);
+
+    let mut odd = map.partition(|&k, _| k % 2 == 0);
+    check_links(&map);
+    check_links(&odd);
+
+    let tail = odd.split_off_after(&15).unwrap();
+    check_links(&odd);
+    check_links(&tail);
+
+    map.extend_map(tail);
+    check_links(&map);
+    map.append(&mut odd);
+    check_links(&map);
+    check_links(&odd);
+
+    assert_eq!(map.insert_before(&0, 100, 100), Ok(None));
+    assert_eq!(map.insert_after(&31, 0, 0), Ok(Some(0)));
+    check_links(&map);
+
+    map.extend_front(vec![(5, 5), (200, 200), (31, 31), (5, 50)]);
+    check_links(&map);
+
+    assert!(map.swap_keys(&100, &0));
+    assert!(map.rotate_to_front(&7));
+    check_links(&map);
+
+    map.dedup_by_value(|a, b| a / 4 == b / 4);
+    check_links(&map);
+    map.retain_while(|&k, _| {
+        if k > 20 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(k % 3 != 0)
+        }
+    });
+    check_links(&map);
+    let removed = map.retain_returning(|&k, _| k % 5 != 0);
+    assert!(!removed.is_empty());
+    check_links(&map);
+
+    map.shrink_to_fit_retaining(2);
+    check_links(&map);
+    map.clear_retaining_capacity();
+    check_links(&map);
+}
@@ -0,0 +1,115 @@
+use hashlink::TwoQueueCache;
+
+#[test]
+fn test_insert_and_get() {
+    let mut cache = TwoQueueCache::new(2, 2);
+    assert_eq!(cache.insert(1, 10), None);
+    assert_eq!(cache.insert(2, 20), None);
+    assert_eq!((cache.recent_len(), cache.frequent_len()), (2, 0));
+
+    assert_eq!(cache.get(&1), Some(&10));
+    assert_eq!((cache.recent_len(), cache.frequent_len()), (1, 1));
+    assert_eq!(cache.insert(2, 21), Some(20));
+    assert_eq!((cache.recent_len(), cache.frequent_len()), (0, 2));
+
+    *cache.get_mut(&2).unwrap() += 1;
+    assert_eq!(cache.peek(&2), Some(&22));
+    assert_eq!(cache.get(&3), None);
+    assert_eq!(cache.len(), 2);
+
+    assert_eq!(cache.remove(&1), Some(10));
+    assert!(!cache.contains(&1));
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_recent_eviction() {
+    let mut cache = TwoQueueCache::new(2, 2);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    cache.insert(3, 3);
+    assert!(!cache.contains(&1));
+    assert!(cache.contains(&2));
+    assert!(cache.contains(&3));
+    assert_eq!(cache.len(), 2);
+
+    // Peeking does not count as an access.
+    assert_eq!(cache.peek(&2), Some(&2));
+    cache.insert(4, 4);
+    assert!(!cache.contains(&2));
+}
+
+#[test]
+fn test_frequent_demotion() {
+    let mut cache = TwoQueueCache::new(2, 2);
+    for i in 0..3 {
+        cache.insert(i, i);
+        cache.get(&i);
+    }
+    // Promoting 2 demoted 0 to the recent queue, where it is the next entry to be evicted.
+    assert_eq!((cache.recent_len(), cache.frequent_len()), (1, 2));
+    assert!(cache.contains(&0));
+
+    cache.insert(3, 3);
+    cache.insert(4, 4);
+    assert!(!cache.contains(&0));
+    assert!(cache.contains(&1) && cache.contains(&2));
+}
+
+#[test]
+fn test_scan_resistance() {
+    let mut cache = TwoQueueCache::new(4, 4);
+    for hot in 0..4 {
+        cache.insert(hot, hot);
+        cache.get(&hot);
+    }
+
+    for cold in 100..1000 {
+        cache.insert(cold, cold);
+    }
+    for hot in 0..4 {
+        assert_eq!(cache.get(&hot), Some(&hot));
+    }
+    assert_eq!(cache.len(), 8);
+}
+
+#[test]
+fn test_zero_frequent_capacity() {
+    let mut cache = TwoQueueCache::new(2, 0);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    assert_eq!(cache.get(&1), Some(&1));
+    assert_eq!(cache.frequent_len(), 0);
+    cache.insert(3, 3);
+    assert!(cache.contains(&1));
+    assert!(!cache.contains(&2));
+}
+
+#[test]
+#[should_panic(expected = "recent capacity must not be zero")]
+fn test_zero_recent_capacity() {
+    let _: TwoQueueCache<i32, i32> = TwoQueueCache::new(0, 2);
+}
+
+#[test]
+#[should_panic(expected = "recent capacity must not be zero")]
+fn test_zero_recent_capacity_with_hasher() {
+    let _: TwoQueueCache<i32, i32> =
+        TwoQueueCache::with_hasher(0, 2, hashbrown::hash_map::DefaultHashBuilder::default());
+}
+
+#[test]
+fn test_minimal_recent_capacity() {
+    let mut cache = TwoQueueCache::new(1, 1);
+    cache.insert(1, 1);
+    assert_eq!(cache.get(&1), Some(&1));
+    assert_eq!(cache.frequent_len(), 1);
+
+    // Promoting a second key demotes the first back into the recent queue.
+    cache.insert(2, 2);
+    assert_eq!(cache.get(&2), Some(&2));
+    assert!(cache.contains(&1));
+    assert_eq!(cache.recent_len(), 1);
+    assert_eq!(cache.frequent_len(), 1);
+}